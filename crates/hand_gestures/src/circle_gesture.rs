@@ -0,0 +1,242 @@
+use std::collections::VecDeque;
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use bevy::math::{Mat3, Vec2, Vec3};
+use bevy::prelude::{Event, Resource};
+
+use crate::gesture::Gesture;
+use crate::models::HandType;
+use crate::{as_micros, HandHistory, HandSample};
+
+/// Smallest number of fingertip positions a circle is fitted to.
+const MIN_CIRCLE_POINTS: usize = 8;
+
+/// Thresholds of circle detection; distances are relative to the hand size (see [`crate::models::HandData::size`]).
+#[derive(Resource, Debug, Clone)]
+pub struct CircleConfig {
+    /// Fingertip positions from this period are used to fit a circle.
+    pub window: Duration,
+    pub min_radius: f32,
+    pub max_radius: f32,
+    /// Largest standard deviation of distances from the center, relative to the radius.
+    pub max_radius_deviation: f32,
+    /// Turns which have to be traced within the window to start the gesture.
+    pub min_turns: f32,
+}
+
+impl Default for CircleConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_millis(1000),
+            min_radius: 0.5,
+            max_radius: 6.0,
+            max_radius_deviation: 0.3,
+            min_turns: 0.75,
+        }
+    }
+}
+
+/// Direction of the circle as seen by the user, i.e. looking along -Z.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CircleDirection {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Sent once per app update while the index finger tip traces a circle, so it can be used as a dial.
+#[derive(Event, Debug, Clone)]
+pub struct CircleGesture {
+    pub hand: HandType,
+    pub center: Vec3,
+    pub radius: f32,
+    /// normal of the circle plane, pointing so the tip moves counter-clockwise around it
+    pub normal: Vec3,
+    pub direction: CircleDirection,
+    /// turns traced since the gesture started, around the normal at its start;
+    /// decreases when the tip goes back in the opposite direction
+    pub progress: f32,
+    /// turns traced since the previous event, negative when going against the direction of the start
+    pub delta: f32,
+}
+
+struct CircleFit {
+    center: Vec3,
+    radius: f32,
+    normal: Vec3,
+    /// Turns traced by the points, positive when going counter-clockwise around the normal.
+    turns: f32,
+}
+
+#[derive(Default)]
+pub struct CircleState {
+    /// Index finger tip positions with timestamps, oldest first. Kept longer than the hands history,
+    /// so circles slower than its capacity are found.
+    trail: VecDeque<(i64, Vec3)>,
+    /// Set while the hand is tracing a circle.
+    active: Option<ActiveCircle>,
+}
+
+struct ActiveCircle {
+    /// Normal of the circle when the gesture started; turns are counted around it, so they keep their sign
+    /// when the direction is reversed.
+    axis: Vec3,
+    /// Turns traced since the gesture started.
+    progress: f32,
+}
+
+impl CircleState {
+    pub fn is_circling(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Turns traced since the gesture started, or `None` if the hand is not tracing a circle.
+    pub fn progress(&self) -> Option<f32> {
+        self.active.as_ref().map(|circle| circle.progress)
+    }
+}
+
+/// Detector of [`CircleGesture`]; fits a circle to the recent index finger tip positions.
+pub struct CircleDetector;
+
+impl Gesture for CircleDetector {
+    const NAME: &'static str = "circle";
+    type Event = CircleGesture;
+    type Config = CircleConfig;
+    type HandState = CircleState;
+
+    fn update(
+        config: &CircleConfig,
+        state: &mut CircleState,
+        hand: HandType,
+        _history: &HandHistory,
+        new_samples: &[&HandSample],
+    ) -> Option<CircleGesture> {
+        let latest = new_samples.last()?;
+        state.trail.extend(
+            new_samples
+                .iter()
+                .map(|sample| (sample.timestamp, sample.hand.index[0])),
+        );
+        while state
+            .trail
+            .front()
+            .is_some_and(|(timestamp, _)| latest.timestamp - timestamp > as_micros(config.window))
+        {
+            state.trail.pop_front();
+        }
+
+        let hand_size = latest.hand.size();
+        let fit = fit_circle(&state.trail, config.max_radius_deviation)
+            .filter(|fit| fit.radius >= config.min_radius * hand_size && fit.radius <= config.max_radius * hand_size);
+        let Some(fit) = fit.filter(|fit| state.active.is_some() || fit.turns >= config.min_turns) else {
+            state.active = None;
+            return None;
+        };
+
+        let is_started = state.active.is_none();
+        let active = state.active.get_or_insert(ActiveCircle {
+            axis: fit.normal,
+            progress: 0.0,
+        });
+        let delta = match is_started {
+            // the first event reports turns traced in the whole window
+            true => fit.turns,
+            false => {
+                let recent_points = state.trail.len().saturating_sub(new_samples.len() + 1);
+                let offsets = state
+                    .trail
+                    .iter()
+                    .skip(recent_points)
+                    .map(|(_, point)| *point - fit.center)
+                    .collect::<Vec<_>>();
+                offsets
+                    .windows(2)
+                    .map(|pair| signed_angle(pair[0], pair[1], active.axis))
+                    .sum::<f32>()
+                    / TAU
+            }
+        };
+        active.progress += delta;
+
+        Some(CircleGesture {
+            hand,
+            center: fit.center,
+            radius: fit.radius,
+            normal: fit.normal,
+            direction: if fit.normal.z >= 0.0 {
+                CircleDirection::CounterClockwise
+            } else {
+                CircleDirection::Clockwise
+            },
+            progress: active.progress,
+            delta,
+        })
+    }
+}
+
+/// Fits a circle to `trail` (least squares fit in the plane of the movement),
+/// or returns `None` if the points do not lie on a circle.
+fn fit_circle(trail: &VecDeque<(i64, Vec3)>, max_radius_deviation: f32) -> Option<CircleFit> {
+    if trail.len() < MIN_CIRCLE_POINTS {
+        return None;
+    }
+
+    let points = trail.iter().map(|(_, point)| *point).collect::<Vec<_>>();
+    let centroid = points.iter().sum::<Vec3>() / points.len() as f32;
+
+    // sum of triangle areas spanned by consecutive points, its direction is the normal of the traced plane
+    let area = points
+        .windows(2)
+        .map(|pair| (pair[0] - centroid).cross(pair[1] - centroid))
+        .sum::<Vec3>();
+    let normal = area.try_normalize()?;
+    let u = normal.any_orthonormal_vector();
+    let v = normal.cross(u);
+    let planar = points
+        .iter()
+        .map(|point| Vec2::new((*point - centroid).dot(u), (*point - centroid).dot(v)))
+        .collect::<Vec<_>>();
+
+    // x^2 + y^2 + d * x + e * y + f = 0
+    let (mut normal_matrix, mut right_side) = (Mat3::ZERO, Vec3::ZERO);
+    for point in &planar {
+        let row = point.extend(1.0);
+        normal_matrix += Mat3::from_cols(row * row.x, row * row.y, row * row.z);
+        right_side -= row * point.length_squared();
+    }
+    if normal_matrix.determinant().abs() <= f32::EPSILON {
+        return None;
+    }
+    let solution = normal_matrix.inverse() * right_side;
+    let planar_center = Vec2::new(-solution.x / 2.0, -solution.y / 2.0);
+    let radius = (planar_center.length_squared() - solution.z).max(0.0).sqrt();
+
+    let offsets = planar.iter().map(|point| *point - planar_center).collect::<Vec<_>>();
+    let variance = offsets
+        .iter()
+        .map(|offset| (offset.length() - radius).powi(2))
+        .sum::<f32>()
+        / offsets.len() as f32;
+    if radius <= f32::EPSILON || variance.sqrt() / radius > max_radius_deviation {
+        return None;
+    }
+
+    let turns = offsets
+        .windows(2)
+        .map(|pair| pair[0].perp_dot(pair[1]).atan2(pair[0].dot(pair[1])))
+        .sum::<f32>()
+        / TAU;
+
+    Some(CircleFit {
+        center: centroid + u * planar_center.x + v * planar_center.y,
+        radius,
+        normal,
+        turns,
+    })
+}
+
+/// Angle from `from` to `to`, positive when counter-clockwise around `normal`.
+fn signed_angle(from: Vec3, to: Vec3, normal: Vec3) -> f32 {
+    normal.dot(from.cross(to)).atan2(from.dot(to))
+}
@@ -0,0 +1,501 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use bevy::app::{App, Update};
+use bevy::math::Vec3;
+use bevy::prelude::{Event, EventWriter, IntoSystemConfigs, Res, ResMut, Resource};
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::gesture::register_gesture_name;
+use crate::models::HandType;
+use crate::{as_micros, DetectGestures, HandsData};
+
+/// Version of the templates file format written by [`GestureTemplates::save`].
+pub const TEMPLATES_FORMAT_VERSION: u32 = 1;
+const TEMPLATES_FORMAT_NAME: &str = "hand_gesture_templates";
+/// Number of points every trajectory is resampled to before matching.
+const TRAJECTORY_POINTS: usize = 32;
+/// Durations of the matched movement relative to the duration of a template,
+/// so a gesture performed a bit faster or slower than recorded is recognized too.
+const DURATION_SCALES: [f32; 3] = [0.75, 1.0, 1.3];
+
+/// Limits of matching the index finger tip movement against [`GestureTemplates`] in [`detect_custom_gesture`].
+/// Only the shape of the path is compared, so `min_path_length` keeps small movements from being matched.
+#[derive(Resource, Debug, Clone)]
+pub struct CustomGestureConfig {
+    /// Lowest score (see [`CustomGesture::score`]) of a recognized gesture.
+    pub min_score: f32,
+    /// Shortest path of the index finger tip, relative to the hand size (see [`crate::models::HandData::size`]),
+    /// matched against templates; filters out a still hand, whose jitter can look like any gesture.
+    pub min_path_length: f32,
+    /// Time after a recognized gesture in which the hand cannot trigger another one.
+    pub cooldown: Duration,
+}
+
+impl Default for CustomGestureConfig {
+    fn default() -> Self {
+        Self {
+            min_score: 0.8,
+            min_path_length: 3.0,
+            cooldown: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Sent when the recent index finger tip movement matches a template from [`GestureTemplates`].
+#[derive(Event, Debug, Clone)]
+pub struct CustomGesture {
+    pub hand: HandType,
+    /// name of the matching template
+    pub name: String,
+    /// similarity of the movement and the template, from 0 to 1
+    pub score: f32,
+}
+
+/// Adds recording of [`GestureTemplates`] and recognition of [`CustomGesture`].
+/// Added only once; listed as "custom" in [`crate::gesture::RegisteredGestures`].
+pub fn add_custom_gestures(app: &mut App) {
+    if !register_gesture_name(app, "custom") {
+        return;
+    }
+
+    app.add_event::<CustomGesture>()
+        .init_resource::<CustomGestureConfig>()
+        .init_resource::<GestureTemplates>()
+        .init_resource::<TemplateRecorder>()
+        .insert_resource(CustomGestureInfo::new())
+        .add_systems(
+            Update,
+            (record_gesture_template, detect_custom_gesture)
+                .chain()
+                .in_set(DetectGestures),
+        );
+}
+
+#[derive(Debug)]
+pub enum TemplatesError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    UnknownFormat,
+    UnsupportedVersion(u32),
+}
+
+impl Display for TemplatesError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TemplatesError::Io(error) => write!(f, "gesture templates io error: {error}"),
+            TemplatesError::Parse(error) => write!(f, "invalid gesture templates: {error}"),
+            TemplatesError::UnknownFormat => write!(f, "file is not a gesture templates file"),
+            TemplatesError::UnsupportedVersion(version) => write!(
+                f,
+                "gesture templates version {version} is newer than supported version {TEMPLATES_FORMAT_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TemplatesError {}
+
+impl From<std::io::Error> for TemplatesError {
+    fn from(error: std::io::Error) -> Self {
+        TemplatesError::Io(error)
+    }
+}
+
+/// Single recorded example of a gesture. Several templates can share a name,
+/// more examples make recognition of the gesture more reliable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GestureTemplate {
+    pub name: String,
+    /// duration of the recorded movement
+    pub duration: Duration,
+    /// index finger tip positions, resampled and normalized by [`normalize_trajectory`]
+    points: Vec<[f32; 3]>,
+}
+
+impl GestureTemplate {
+    /// Creates a template from index finger tip positions with timestamps in microseconds, oldest first.
+    /// Returns `None` if the fingertip did not move.
+    pub fn from_trajectory(name: impl Into<String>, trajectory: &[(i64, Vec3)]) -> Option<Self> {
+        let (first, last) = (trajectory.first()?, trajectory.last()?);
+        let points = trajectory.iter().map(|(_, point)| *point).collect::<Vec<_>>();
+
+        Some(Self {
+            name: name.into(),
+            duration: Duration::from_micros((last.0 - first.0).max(0) as u64),
+            points: normalize_trajectory(&points)?
+                .iter()
+                .map(|point| point.to_array())
+                .collect(),
+        })
+    }
+
+    /// Returns similarity of normalized points and the template, from 0 to 1.
+    fn score(&self, points: &[Vec3]) -> f32 {
+        let distance = self
+            .points
+            .iter()
+            .zip(points)
+            .map(|(template_point, point)| Vec3::from_array(*template_point).distance(*point))
+            .sum::<f32>()
+            / TRAJECTORY_POINTS as f32;
+        // normalized points lie in a unit cube, so half of its diagonal is the distance of unrelated trajectories
+        let half_diagonal = 0.5 * 3f32.sqrt();
+
+        (1.0 - distance / half_diagonal).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TemplatesFile {
+    format: String,
+    version: u32,
+    templates: Vec<GestureTemplate>,
+}
+
+/// Templates matched by [`detect_custom_gesture`]; recorded with [`TemplateRecorder`] or loaded from a file.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct GestureTemplates {
+    templates: Vec<GestureTemplate>,
+}
+
+impl GestureTemplates {
+    pub fn add(&mut self, template: GestureTemplate) {
+        self.templates.push(template);
+    }
+
+    /// Removes all templates with the name.
+    pub fn remove(&mut self, name: &str) {
+        self.templates.retain(|template| template.name != name);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &GestureTemplate> {
+        self.templates.iter()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TemplatesError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let file = TemplatesFile {
+            format: TEMPLATES_FORMAT_NAME.to_string(),
+            version: TEMPLATES_FORMAT_VERSION,
+            templates: self.templates.clone(),
+        };
+        serde_json::to_writer(&mut writer, &file).map_err(TemplatesError::Parse)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TemplatesError> {
+        let file: TemplatesFile =
+            serde_json::from_reader(BufReader::new(File::open(path)?)).map_err(TemplatesError::Parse)?;
+        if file.format != TEMPLATES_FORMAT_NAME {
+            return Err(TemplatesError::UnknownFormat);
+        }
+        if file.version > TEMPLATES_FORMAT_VERSION {
+            return Err(TemplatesError::UnsupportedVersion(file.version));
+        }
+
+        Ok(Self {
+            templates: file.templates,
+        })
+    }
+
+    /// Longest duration of the movement matched against any template.
+    fn longest_match(&self) -> Duration {
+        let longest_scale = DURATION_SCALES.iter().copied().fold(0.0, f32::max);
+        self.templates
+            .iter()
+            .map(|template| template.duration.mul_f32(longest_scale))
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+struct RecordingTemplate {
+    name: String,
+    /// Recorded hand; the right hand is preferred, if both are tracked when the recording starts.
+    hand: Option<HandType>,
+    trajectory: Vec<(i64, Vec3)>,
+}
+
+/// Records the index finger tip of a single hand, to create a [`GestureTemplate`] from the movement.
+#[derive(Resource, Default)]
+pub struct TemplateRecorder {
+    recording: Option<RecordingTemplate>,
+}
+
+impl TemplateRecorder {
+    pub fn start(&mut self, name: impl Into<String>) {
+        self.recording = Some(RecordingTemplate {
+            name: name.into(),
+            hand: None,
+            trajectory: Vec::new(),
+        });
+    }
+
+    /// Stops recording and returns the template, or `None` if no movement was recorded.
+    /// The template has to be added to [`GestureTemplates`] to be recognized.
+    pub fn stop(&mut self) -> Option<GestureTemplate> {
+        let recording = self.recording.take()?;
+        GestureTemplate::from_trajectory(recording.name, &recording.trajectory)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+}
+
+pub(crate) fn record_gesture_template(hands_data: Res<HandsData>, mut recorder: ResMut<TemplateRecorder>) {
+    let Some(recording) = recorder.recording.as_mut() else {
+        return;
+    };
+    let Some(hand) = recording.hand.or_else(|| {
+        [HandType::Right, HandType::Left]
+            .into_iter()
+            .find(|hand| hands_data.history(*hand).is_some())
+    }) else {
+        return;
+    };
+    let Some(history) = hands_data.history(hand) else {
+        return;
+    };
+    recording.hand = Some(hand);
+
+    let last_timestamp = recording
+        .trajectory
+        .last()
+        .map_or(i64::MIN, |(timestamp, _)| *timestamp);
+    recording.trajectory.extend(
+        history
+            .samples_after(last_timestamp)
+            .map(|sample| (sample.timestamp, sample.hand.index[0])),
+    );
+}
+
+#[derive(Default)]
+struct HandTrail {
+    /// Index finger tip positions with timestamps, oldest first.
+    points: VecDeque<(i64, Vec3)>,
+    /// Timestamp of the frame in which the last gesture was recognized.
+    last_gesture: Option<i64>,
+}
+
+/// Keeps fingertip trails as long as the longest template, which is usually longer than the hands history.
+#[derive(Resource, Default)]
+pub struct CustomGestureInfo {
+    hands: HashMap<HandType, HandTrail>,
+}
+
+impl CustomGestureInfo {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Finding custom gestures, by matching the recent index finger tip trajectory against every template,
+/// in the way of the $1 recognizer: both trajectories are resampled to the same number of points,
+/// normalized in position and size, and compared point by point.
+pub fn detect_custom_gesture(
+    hands_data: Res<HandsData>,
+    templates: Res<GestureTemplates>,
+    config: Res<CustomGestureConfig>,
+    mut custom_gesture_info: ResMut<CustomGestureInfo>,
+    mut custom_gesture: EventWriter<CustomGesture>,
+) {
+    let longest_match = as_micros(templates.longest_match());
+
+    for hand in [HandType::Left, HandType::Right] {
+        let Some(history) = hands_data.history(hand) else {
+            custom_gesture_info.hands.remove(&hand);
+            continue;
+        };
+        let Some(latest) = history.latest_sample() else {
+            continue;
+        };
+
+        let trail = custom_gesture_info.hands.entry(hand).or_default();
+        let last_timestamp = trail.points.back().map_or(i64::MIN, |(timestamp, _)| *timestamp);
+        trail.points.extend(
+            history
+                .samples_after(last_timestamp)
+                .map(|sample| (sample.timestamp, sample.hand.index[0])),
+        );
+        while trail
+            .points
+            .front()
+            .is_some_and(|(timestamp, _)| latest.timestamp - timestamp > longest_match)
+        {
+            trail.points.pop_front();
+        }
+
+        if trail
+            .last_gesture
+            .is_some_and(|timestamp| latest.timestamp - timestamp < as_micros(config.cooldown))
+        {
+            continue;
+        }
+
+        let min_path_length = config.min_path_length * latest.hand.size();
+        let best_match = templates
+            .iter()
+            .flat_map(|template| DURATION_SCALES.map(|scale| (template, template.duration.mul_f32(scale))))
+            .filter_map(|(template, duration)| {
+                let start = latest.timestamp - as_micros(duration);
+                // the trail has to cover the whole movement, not only its end
+                let is_covered = trail.points.front().is_some_and(|(timestamp, _)| *timestamp <= start);
+                if !is_covered {
+                    return None;
+                }
+
+                let points = trail
+                    .points
+                    .iter()
+                    .filter(|(timestamp, _)| *timestamp >= start)
+                    .map(|(_, point)| *point)
+                    .collect::<Vec<_>>();
+                if path_length(&points) < min_path_length {
+                    return None;
+                }
+
+                normalize_trajectory(&points).map(|points| (template, template.score(&points)))
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        if let Some((template, score)) = best_match.filter(|(_, score)| *score >= config.min_score) {
+            trail.last_gesture = Some(latest.timestamp);
+            // the matched movement must not be a part of the next gesture
+            trail.points.clear();
+            custom_gesture.send(CustomGesture {
+                hand,
+                name: template.name.clone(),
+                score,
+            });
+        }
+    }
+}
+
+/// Resamples `points` to [`TRAJECTORY_POINTS`] points evenly spaced along the path, moves their centroid
+/// to the origin and scales them, so the largest side of their bounding box is 1.
+/// Returns `None` if the points do not form a path.
+pub fn normalize_trajectory(points: &[Vec3]) -> Option<Vec<Vec3>> {
+    let length = path_length(points);
+    if length <= f32::EPSILON {
+        return None;
+    }
+
+    let resampled = resample(points, length / (TRAJECTORY_POINTS - 1) as f32);
+    let centroid = resampled.iter().sum::<Vec3>() / resampled.len() as f32;
+    let (min, max) = resampled
+        .iter()
+        .fold((Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)), |(min, max), point| {
+            (min.min(*point), max.max(*point))
+        });
+    let size = (max - min).max_element();
+    if size <= f32::EPSILON {
+        return None;
+    }
+
+    Some(resampled.iter().map(|point| (*point - centroid) / size).collect())
+}
+
+fn path_length(points: &[Vec3]) -> f32 {
+    points.windows(2).map(|pair| pair[0].distance(pair[1])).sum()
+}
+
+/// Returns [`TRAJECTORY_POINTS`] points `interval` apart along the path.
+fn resample(points: &[Vec3], interval: f32) -> Vec<Vec3> {
+    let mut resampled = Vec::with_capacity(TRAJECTORY_POINTS);
+    resampled.push(points[0]);
+
+    let mut previous = points[0];
+    let mut accumulated = 0.0;
+    for point in &points[1..] {
+        let mut segment = previous.distance(*point);
+        while accumulated + segment >= interval && resampled.len() < TRAJECTORY_POINTS {
+            previous = previous.lerp(*point, (interval - accumulated) / segment);
+            resampled.push(previous);
+            segment = previous.distance(*point);
+            accumulated = 0.0;
+        }
+        accumulated += segment;
+        previous = *point;
+    }
+
+    // rounding errors can leave the last point out
+    let last = points[points.len() - 1];
+    resampled.resize(TRAJECTORY_POINTS, last);
+    resampled
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::*;
+
+    /// Index finger tip going once around a circle in the XY plane, with `count` frames 10 ms apart.
+    fn circle(center: Vec3, radius: f32, count: usize) -> Vec<(i64, Vec3)> {
+        (0..count)
+            .map(|i| {
+                let angle = TAU * i as f32 / (count - 1) as f32;
+                let point = center + radius * Vec3::new(angle.cos(), angle.sin(), 0.0);
+                (i as i64 * 10_000, point)
+            })
+            .collect()
+    }
+
+    fn score(template: &GestureTemplate, trajectory: &[(i64, Vec3)]) -> f32 {
+        let points = trajectory.iter().map(|(_, point)| *point).collect::<Vec<_>>();
+        template.score(&normalize_trajectory(&points).unwrap())
+    }
+
+    #[test]
+    fn same_path_sampled_differently_scores_near_one() {
+        let template = GestureTemplate::from_trajectory("circle", &circle(Vec3::ZERO, 50.0, 40)).unwrap();
+
+        // bigger, elsewhere and with more frames, as if performed slower
+        let movement = circle(Vec3::new(100.0, 200.0, -30.0), 80.0, 150);
+
+        assert!(score(&template, &movement) > 0.97, "{}", score(&template, &movement));
+    }
+
+    #[test]
+    fn unrelated_path_scores_low() {
+        let template = GestureTemplate::from_trajectory("circle", &circle(Vec3::ZERO, 50.0, 40)).unwrap();
+
+        let line = (0..40)
+            .map(|i| (i as i64 * 10_000, Vec3::new(0.0, 0.0, -3.0 * i as f32)))
+            .collect::<Vec<_>>();
+
+        let min_score = CustomGestureConfig::default().min_score;
+        assert!(score(&template, &line) < min_score - 0.2, "{}", score(&template, &line));
+    }
+
+    #[test]
+    fn still_fingertip_is_not_template() {
+        let trajectory = [(0, Vec3::ONE), (10_000, Vec3::ONE)];
+
+        assert!(GestureTemplate::from_trajectory("still", &trajectory).is_none());
+    }
+
+    #[test]
+    fn normalized_trajectory_is_resampled_and_fits_unit_cube() {
+        let points = circle(Vec3::new(100.0, 200.0, 0.0), 80.0, 7)
+            .iter()
+            .map(|(_, point)| *point)
+            .collect::<Vec<_>>();
+
+        let normalized = normalize_trajectory(&points).unwrap();
+
+        assert_eq!(normalized.len(), TRAJECTORY_POINTS);
+        let centroid = normalized.iter().sum::<Vec3>() / normalized.len() as f32;
+        assert!(centroid.abs_diff_eq(Vec3::ZERO, 1e-4));
+        assert!(normalized.iter().all(|point| point.abs().max_element() <= 1.0));
+    }
+}
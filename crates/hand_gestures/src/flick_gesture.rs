@@ -1,33 +1,269 @@
-use bevy::prelude::{Event, EventWriter, Res, Transform};
-use ringbuf::Rb;
+use std::time::Duration;
 
-use crate::HandsData;
-use crate::models::{Gesture, HandType};
+use bevy::math::Vec3;
+use bevy::prelude::{Event, Resource, Transform};
+
+use crate::gesture::Gesture;
+use crate::models::{finger_extension, HandType};
+use crate::{as_micros, HandHistory, HandSample};
+
+/// Only frames from this period are analyzed.
+const FLICK_GESTURE_WINDOW: Duration = Duration::from_millis(250);
+/// Longest time between the last bent and the first straight frame, in microseconds.
+const FLICK_MAX_DURATION: i64 = 150_000;
+const BENT_EXTENSION_THRESHOLD: f32 = 0.75;
+const STRAIGHT_EXTENSION_THRESHOLD: f32 = 0.95;
+
+/// Settings of [`FlickDetector`]. The extension thresholds of a bent and a straight finger are fixed;
+/// only how often a hand can flick is configurable.
+#[derive(Resource, Debug, Clone)]
+pub struct FlickConfig {
+    /// Time after a flick in which the hand cannot flick again.
+    pub min_interval: Duration,
+}
+
+impl Default for FlickConfig {
+    fn default() -> Self {
+        Self {
+            min_interval: Duration::from_millis(500),
+        }
+    }
+}
 
 #[derive(Event, Debug, Clone)]
 pub struct FlickGesture {
     pub hand_type: HandType,
+    /// the index finger tip, when the finger was bent, looking along the finger
     pub bent_transform: Transform,
+    /// the index finger tip, when the finger got straight, looking along the finger
     pub straight_transform: Transform,
 }
 
-impl Gesture for FlickGesture {}
+#[derive(Default)]
+pub struct FlickState {
+    /// Timestamp of the frame in which the last flick was found, in microseconds.
+    last_flick: Option<i64>,
+}
+
+enum Stage<'a> {
+    BeforeFlick,
+    Flicking {
+        bent: &'a HandSample,
+    },
+    AfterFlick {
+        bent: &'a HandSample,
+        straight: &'a HandSample,
+    },
+}
+
+/// Detector of [`FlickGesture`], checking if recent hands history contains a bent index finger,
+/// which gets straight in less than [`FLICK_MAX_DURATION`].
+/// Example index finger extension values for flick gesture (oldest -> newest):
+/// [0.6, 0.6, 0.7, 0.85, 0.97, 0.98]
+pub struct FlickDetector;
+
+impl Gesture for FlickDetector {
+    const NAME: &'static str = "flick";
+    type Event = FlickGesture;
+    type Config = FlickConfig;
+    type HandState = FlickState;
+
+    fn update(
+        config: &FlickConfig,
+        state: &mut FlickState,
+        _hand: HandType,
+        history: &HandHistory,
+        new_samples: &[&HandSample],
+    ) -> Option<FlickGesture> {
+        let latest = new_samples.last()?;
+        if state
+            .last_flick
+            .is_some_and(|timestamp| latest.timestamp - timestamp < as_micros(config.min_interval))
+        {
+            return None;
+        }
+
+        // frames of the last flick are skipped, so one movement is not reported twice
+        let skipped_until = state.last_flick.unwrap_or(i64::MIN);
+        let gesture = analyze_hand_data(
+            history
+                .samples_within(FLICK_GESTURE_WINDOW)
+                .filter(|sample| sample.timestamp > skipped_until),
+        )?;
+        state.last_flick = Some(latest.timestamp);
+        Some(gesture)
+    }
+}
+
+fn analyze_hand_data<'a>(hand_samples: impl Iterator<Item = &'a HandSample>) -> Option<FlickGesture> {
+    let mut current_stage = Stage::BeforeFlick;
+    for sample in hand_samples {
+        let extension = finger_extension(&sample.hand.index);
+
+        current_stage = match current_stage {
+            _ if extension < BENT_EXTENSION_THRESHOLD => Stage::Flicking { bent: sample },
+            Stage::Flicking { bent } if extension > STRAIGHT_EXTENSION_THRESHOLD => {
+                if sample.timestamp - bent.timestamp <= FLICK_MAX_DURATION {
+                    Stage::AfterFlick { bent, straight: sample }
+                } else {
+                    Stage::BeforeFlick
+                }
+            }
+            stage => stage,
+        };
+    }
+
+    match current_stage {
+        Stage::AfterFlick { bent, straight } => Some(FlickGesture {
+            hand_type: straight.hand.type_,
+            bent_transform: index_tip_transform(bent),
+            straight_transform: index_tip_transform(straight),
+        }),
+        _ => None,
+    }
+}
+
+fn index_tip_transform(sample: &HandSample) -> Transform {
+    let index = &sample.hand.index;
+    let direction = (index[0] - index[1]).normalize_or_zero();
 
-enum Stage {
-    BeforeFlick(usize),
-    Flicking(usize),
-    AfterFlick(usize),
+    Transform::from_translation(index[0]).looking_to(direction, Vec3::Y)
 }
 
-pub fn detect_flick_event(hands_data: Res<HandsData>, mut hand_flick: EventWriter<FlickGesture>) {
-    let (first_hand_iter, second_hand_iter) = hands_data.get_iters();
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::ManualEventReader;
+    use bevy::math::Quat;
+    use bevy::prelude::{App, Events};
 
-    let mut current_stage = Stage::BeforeFlick(0);
-    for hand in first_hand_iter {
-        match current_stage {
-            Stage::BeforeFlick(ref mut val) => {}
-            Stage::Flicking(ref mut val) => {}
-            Stage::AfterFlick(ref mut _val) => {}
+    use super::*;
+    use crate::models::{Finger, HandData};
+    use crate::source::TrackingFrame;
+    use crate::{GesturePlugin, HandsData};
+
+    const BENT: f32 = 60.0;
+    const STRAIGHT: f32 = 0.0;
+    /// Time between frames, in microseconds.
+    const FRAME_INTERVAL: i64 = 10_000;
+
+    /// Index finger pointing towards -Z, with every joint bent by `angle` degrees.
+    fn index_finger(angle: f32) -> Finger {
+        let mut joints = [Vec3::Z, Vec3::Z, Vec3::Z, Vec3::ZERO, Vec3::Z];
+        let mut direction = Vec3::NEG_Z;
+        for joint in (0..3).rev() {
+            joints[joint] = joints[joint + 1] + direction;
+            direction = Quat::from_rotation_x(-angle.to_radians()) * direction;
         }
+        joints
+    }
+
+    fn sample(timestamp: i64, angle: f32) -> HandSample {
+        let finger = index_finger(angle);
+        HandSample {
+            frame_id: timestamp / FRAME_INTERVAL,
+            timestamp,
+            hand: HandData {
+                id: Some(1),
+                type_: HandType::Right,
+                confidence: 1.0,
+                thumb: finger,
+                index: finger,
+                middle: finger,
+                ring: finger,
+                pinky: finger,
+                palm: None,
+                arm: None,
+                grab_strength: None,
+                pinch_strength: None,
+            },
+        }
+    }
+
+    /// Samples from `start` until `end`, in microseconds, with the angle interpolated between `from` and `to`.
+    fn samples(start: i64, end: i64, from: f32, to: f32) -> Vec<HandSample> {
+        (start..end)
+            .step_by(FRAME_INTERVAL as usize)
+            .map(|timestamp| {
+                let t = (timestamp - start) as f32 / (end - start) as f32;
+                sample(timestamp, from + (to - from) * t)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn quickly_straightened_finger_is_flick() {
+        let mut hand_samples = samples(0, 50_000, BENT, BENT);
+        hand_samples.extend(samples(50_000, 150_000, STRAIGHT, STRAIGHT));
+
+        let gesture = analyze_hand_data(hand_samples.iter()).expect("flick was not detected");
+
+        assert_eq!(gesture.hand_type, HandType::Right);
+        assert_eq!(gesture.bent_transform.translation, index_finger(BENT)[0]);
+        assert_eq!(gesture.straight_transform.translation, index_finger(STRAIGHT)[0]);
+    }
+
+    #[test]
+    fn slowly_straightened_finger_is_not_flick() {
+        let mut hand_samples = samples(0, 600_000, BENT, STRAIGHT);
+        hand_samples.extend(samples(600_000, 700_000, STRAIGHT, STRAIGHT));
+
+        assert!(analyze_hand_data(hand_samples.iter()).is_none());
+    }
+
+    /// Runs [`FlickDetector`] in an app, updated after every frame of the finger held at `(start, end, angle)`
+    /// segments, and returns the number of reported flicks.
+    fn count_flicks(config: FlickConfig, segments: &[(i64, i64, f32)]) -> usize {
+        let mut app = App::new();
+        app.add_plugins(GesturePlugin::empty().with_gesture::<FlickDetector>())
+            .insert_resource(config);
+        let mut reader = ManualEventReader::<FlickGesture>::default();
+        let mut flicks = 0;
+
+        for (start, end, angle) in segments {
+            for sample in samples(*start, *end, *angle, *angle) {
+                app.world.resource_mut::<HandsData>().push_overwrite(TrackingFrame {
+                    frame_id: sample.frame_id,
+                    timestamp: sample.timestamp,
+                    hands: [Some(sample.hand), None],
+                });
+                app.update();
+                flicks += reader.read(app.world.resource::<Events<FlickGesture>>()).count();
+            }
+        }
+        flicks
+    }
+
+    #[test]
+    fn flick_within_min_interval_is_suppressed() {
+        // flicks at 50 ms and 200 ms are closer than the min interval, the one at 750 ms is not
+        let segments = [
+            (0, 50_000, BENT),
+            (50_000, 150_000, STRAIGHT),
+            (150_000, 200_000, BENT),
+            (200_000, 700_000, STRAIGHT),
+            (700_000, 750_000, BENT),
+            (750_000, 1_000_000, STRAIGHT),
+        ];
+
+        assert_eq!(count_flicks(FlickConfig::default(), &segments), 2);
+    }
+
+    #[test]
+    fn flick_is_reported_once_without_min_interval() {
+        let config = FlickConfig {
+            min_interval: Duration::ZERO,
+        };
+
+        // the straight finger stays in the analyzed window for longer than the window
+        let one_flick = [(0, 50_000, BENT), (50_000, 500_000, STRAIGHT)];
+        assert_eq!(count_flicks(config.clone(), &one_flick), 1);
+
+        let two_flicks = [
+            (0, 50_000, BENT),
+            (50_000, 150_000, STRAIGHT),
+            (150_000, 200_000, BENT),
+            (200_000, 500_000, STRAIGHT),
+        ];
+        assert_eq!(count_flicks(config, &two_flicks), 2);
     }
 }
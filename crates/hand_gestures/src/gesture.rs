@@ -0,0 +1,156 @@
+use bevy::app::{App, Update};
+use bevy::prelude::{Event, EventWriter, IntoSystemConfigs, Res, ResMut, Resource};
+use bevy::utils::HashMap;
+
+use crate::models::HandType;
+use crate::{DetectGestures, HandHistory, HandSample, HandsData};
+
+/// Detector of a gesture of a single hand. Once added with [`AddGesture::add_gesture`], it is updated
+/// for every tracked hand with frames which arrived since the previous update.
+///
+/// Implementing this trait is enough for a gesture defined outside of this crate to be detected
+/// the same way as the built-in ones.
+pub trait Gesture: Send + Sync + 'static {
+    /// Unique name of the gesture, see [`RegisteredGestures`].
+    const NAME: &'static str;
+    /// Event sent when the gesture is detected.
+    type Event: Event;
+    /// Thresholds of the detection; inserted as a resource, so they can be changed at runtime.
+    type Config: Resource + Default;
+    /// State of a single hand kept between updates; dropped when the tracker loses the hand.
+    type HandState: Default + Send + Sync + 'static;
+
+    /// Processes frames of `hand` which arrived since the previous update. `new_samples` are ordered
+    /// from the oldest and never empty; `history` contains also the frames processed before.
+    fn update(
+        config: &Self::Config,
+        state: &mut Self::HandState,
+        hand: HandType,
+        history: &HandHistory,
+        new_samples: &[&HandSample],
+    ) -> Option<Self::Event>;
+
+    /// Called when the tracker loses the hand, with its last state; e.g. to end a gesture in progress.
+    fn hand_lost(_config: &Self::Config, _state: Self::HandState, _hand: HandType) -> Option<Self::Event> {
+        None
+    }
+}
+
+struct HandGestureState<S> {
+    state: S,
+    /// Timestamp of the newest frame passed to [`Gesture::update`].
+    last_timestamp: i64,
+}
+
+/// States of a [`Gesture`] for the tracked hands.
+#[derive(Resource)]
+pub struct GestureStates<G: Gesture> {
+    hands: HashMap<HandType, HandGestureState<G::HandState>>,
+}
+
+impl<G: Gesture> GestureStates<G> {
+    /// Returns the state of the hand, or `None` if it is not tracked.
+    pub fn get(&self, hand_type: HandType) -> Option<&G::HandState> {
+        self.hands.get(&hand_type).map(|hand| &hand.state)
+    }
+}
+
+impl<G: Gesture> Default for GestureStates<G> {
+    fn default() -> Self {
+        Self {
+            hands: HashMap::default(),
+        }
+    }
+}
+
+/// Names of the gestures added to the app, in the order they were added.
+#[derive(Resource, Default, Debug)]
+pub struct RegisteredGestures {
+    names: Vec<&'static str>,
+}
+
+impl RegisteredGestures {
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.names.iter().copied()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(&name)
+    }
+
+    /// Adds the name, unless it is already registered; returns true if it was added.
+    pub(crate) fn register(&mut self, name: &'static str) -> bool {
+        if self.contains(name) {
+            return false;
+        }
+
+        self.names.push(name);
+        true
+    }
+}
+
+pub trait AddGesture {
+    /// Adds the event, the config and the detection system of a gesture.
+    /// A gesture with the same name is added only once.
+    fn add_gesture<G: Gesture>(&mut self) -> &mut Self;
+}
+
+impl AddGesture for App {
+    fn add_gesture<G: Gesture>(&mut self) -> &mut Self {
+        if !register_gesture_name(self, G::NAME) {
+            return self;
+        }
+
+        self.add_event::<G::Event>()
+            .init_resource::<G::Config>()
+            .init_resource::<GestureStates<G>>()
+            .add_systems(Update, detect_gesture::<G>.in_set(DetectGestures))
+    }
+}
+
+/// Adds `name` to [`RegisteredGestures`] of `app`; returns false if it was already there.
+/// Used by gestures which are not implemented with [`Gesture`], so they are listed as well.
+pub(crate) fn register_gesture_name(app: &mut App, name: &'static str) -> bool {
+    app.world
+        .get_resource_or_insert_with(RegisteredGestures::default)
+        .register(name)
+}
+
+/// Adds a gesture to `app`; usable as a function pointer, see [`crate::GesturePlugin::with_gestures`].
+pub(crate) fn add_gesture<G: Gesture>(app: &mut App) {
+    app.add_gesture::<G>();
+}
+
+fn detect_gesture<G: Gesture>(
+    hands_data: Res<HandsData>,
+    config: Res<G::Config>,
+    mut states: ResMut<GestureStates<G>>,
+    mut events: EventWriter<G::Event>,
+) {
+    for hand in [HandType::Left, HandType::Right] {
+        let Some(history) = hands_data.history(hand) else {
+            if let Some(event) = states
+                .hands
+                .remove(&hand)
+                .and_then(|lost| G::hand_lost(&config, lost.state, hand))
+            {
+                events.send(event);
+            }
+            continue;
+        };
+
+        let hand_state = states.hands.entry(hand).or_insert_with(|| HandGestureState {
+            state: G::HandState::default(),
+            last_timestamp: i64::MIN,
+        });
+        let new_samples = history.samples_after(hand_state.last_timestamp).collect::<Vec<_>>();
+        let Some(latest) = new_samples.last() else {
+            continue;
+        };
+        hand_state.last_timestamp = latest.timestamp;
+
+        if let Some(event) = G::update(&config, &mut hand_state.state, hand, history, &new_samples) {
+            events.send(event);
+        }
+    }
+}
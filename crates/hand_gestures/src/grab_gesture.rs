@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use bevy::math::Vec3;
+use bevy::prelude::{Event, Resource, Transform};
+
+use crate::gesture::Gesture;
+use crate::models::{HandData, HandType};
+use crate::{HandHistory, HandSample};
+
+/// Hysteresis of [`GrabDetector`]. It is applied to the curl of the fingers averaged over `smoothing_window`,
+/// so a single noisy frame can neither start nor release a grab.
+#[derive(Resource, Debug, Clone)]
+pub struct GrabConfig {
+    /// Curl of fingers (see [`HandData::grab_curl`]) above which a grab starts.
+    pub start_threshold: f32,
+    /// Curl of fingers below which a grab is released; lower than the start threshold,
+    /// so noise around a single threshold does not produce repeated grabs.
+    pub release_threshold: f32,
+    /// Curl is averaged over this period to reduce jitter.
+    pub smoothing_window: Duration,
+}
+
+impl Default for GrabConfig {
+    fn default() -> Self {
+        Self {
+            start_threshold: 0.75,
+            release_threshold: 0.55,
+            smoothing_window: Duration::from_millis(50),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GrabPhase {
+    Started,
+    /// Sent once per app update while the hand stays closed.
+    Held,
+    /// Sent when the hand opens or is lost by the tracker.
+    Released,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct GrabEvent {
+    pub hand_type: HandType,
+    pub phase: GrabPhase,
+    /// the center of the closed hand, oriented along the palm
+    pub transform: Transform,
+    /// time elapsed since the grab started
+    pub duration: Duration,
+}
+
+struct ActiveGrab {
+    /// Timestamp of the frame in which the grab started, in microseconds.
+    started_at: i64,
+    /// Timestamp of the newest frame of the grab, in microseconds.
+    updated_at: i64,
+    transform: Transform,
+}
+
+impl ActiveGrab {
+    fn duration(&self) -> Duration {
+        Duration::from_micros((self.updated_at - self.started_at).max(0) as u64)
+    }
+}
+
+#[derive(Default)]
+pub struct GrabState {
+    active_grab: Option<ActiveGrab>,
+}
+
+impl GrabState {
+    pub fn is_grabbing(&self) -> bool {
+        self.active_grab.is_some()
+    }
+}
+
+/// Detector of [`GrabEvent`], checking how much all four fingers are curled towards the palm.
+/// A grab lasts from the moment the curl exceeds [`GrabConfig::start_threshold`]
+/// until it drops below [`GrabConfig::release_threshold`].
+pub struct GrabDetector;
+
+impl Gesture for GrabDetector {
+    const NAME: &'static str = "grab";
+    type Event = GrabEvent;
+    type Config = GrabConfig;
+    type HandState = GrabState;
+
+    fn update(
+        config: &GrabConfig,
+        state: &mut GrabState,
+        hand_type: HandType,
+        history: &HandHistory,
+        new_samples: &[&HandSample],
+    ) -> Option<GrabEvent> {
+        let latest = new_samples.last()?;
+        let (count, curl_sum) = history
+            .samples_within(config.smoothing_window)
+            .fold((0, 0.0), |(count, sum), sample| {
+                (count + 1, sum + sample.hand.grab_curl())
+            });
+        let curl = curl_sum / count as f32;
+        let transform = grab_transform(&latest.hand);
+
+        match state.active_grab.as_mut() {
+            Some(grab) => {
+                grab.updated_at = latest.timestamp;
+                grab.transform = transform;
+                let phase = if curl >= config.release_threshold {
+                    GrabPhase::Held
+                } else {
+                    GrabPhase::Released
+                };
+                let event = GrabEvent {
+                    hand_type,
+                    phase,
+                    transform,
+                    duration: grab.duration(),
+                };
+                if phase == GrabPhase::Released {
+                    state.active_grab = None;
+                }
+                Some(event)
+            }
+            None if curl > config.start_threshold => {
+                state.active_grab = Some(ActiveGrab {
+                    started_at: latest.timestamp,
+                    updated_at: latest.timestamp,
+                    transform,
+                });
+                Some(GrabEvent {
+                    hand_type,
+                    phase: GrabPhase::Started,
+                    transform,
+                    duration: Duration::ZERO,
+                })
+            }
+            None => None,
+        }
+    }
+
+    fn hand_lost(_config: &GrabConfig, state: GrabState, hand_type: HandType) -> Option<GrabEvent> {
+        let grab = state.active_grab?;
+        Some(GrabEvent {
+            hand_type,
+            phase: GrabPhase::Released,
+            transform: grab.transform,
+            duration: grab.duration(),
+        })
+    }
+}
+
+/// Point between the palm center and the finger tips, looking along the palm.
+fn grab_transform(hand: &HandData) -> Transform {
+    let palm_center = hand.palm_center();
+    let tips_center = hand.fingers().iter().map(|finger| finger[0]).sum::<Vec3>() / 4.0;
+    let center = palm_center.lerp(tips_center, 0.5);
+
+    match hand.palm {
+        Some(palm) => Transform::from_translation(center).looking_to(palm.direction, -palm.normal),
+        None => Transform::from_translation(center),
+    }
+}
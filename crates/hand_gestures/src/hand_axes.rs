@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use bevy::app::{App, Update};
+use bevy::prelude::{IntoSystemConfigs, Res, ResMut, Resource};
+use bevy::utils::HashMap;
+
+use crate::gesture::register_gesture_name;
+use crate::models::{finger_curl, finger_extension, HandData, HandType};
+use crate::pinch_calibration::PinchCalibration;
+use crate::pinch_gesture::{pinch_strength, PinchFinger};
+use crate::{DetectGestures, HandsData};
+
+/// Extension of a fully curled finger, see [`finger_extension`].
+const CURLED_FINGER_EXTENSION: f32 = 0.3;
+
+/// Analog values of a single hand, each from 0 to 1.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct HandAxisValues {
+    /// Pinch strength of index, middle, ring and pinky fingers, in the order of [`PinchFinger::ALL`].
+    pub pinch: [f32; 4],
+    /// How much the four fingers are curled towards the palm, see [`HandData::grab_curl`].
+    pub grab: f32,
+    /// How straight all five fingers are; 1 for a flat, open hand.
+    pub palm_openness: f32,
+    /// Curl of thumb, index, middle, ring and pinky fingers, see [`finger_curl`].
+    pub curls: [f32; 5],
+}
+
+impl HandAxisValues {
+    pub fn from_hand(hand: &HandData, calibration: &PinchCalibration) -> Self {
+        let fingers = [&hand.thumb, &hand.index, &hand.middle, &hand.ring, &hand.pinky];
+        let palm_center = hand.palm_center();
+        let openness = fingers
+            .iter()
+            .map(|finger| (finger_extension(finger) - CURLED_FINGER_EXTENSION) / (1.0 - CURLED_FINGER_EXTENSION))
+            .sum::<f32>()
+            / fingers.len() as f32;
+
+        Self {
+            pinch: PinchFinger::ALL.map(|finger| pinch_strength(hand, finger, calibration)),
+            grab: hand.grab_curl(),
+            palm_openness: openness.clamp(0.0, 1.0),
+            curls: fingers.map(|finger| finger_curl(finger, palm_center)),
+        }
+    }
+
+    pub fn pinch_strength(&self, finger: PinchFinger) -> f32 {
+        self.pinch[finger as usize]
+    }
+
+    /// Moves values towards `target`; `t` = 1 returns `target`.
+    fn approach(&mut self, target: &HandAxisValues, t: f32) {
+        let lerp = |value: &mut f32, target: f32| *value += (target - *value) * t;
+
+        for (value, target) in self.pinch.iter_mut().zip(target.pinch) {
+            lerp(value, target);
+        }
+        lerp(&mut self.grab, target.grab);
+        lerp(&mut self.palm_openness, target.palm_openness);
+        for (value, target) in self.curls.iter_mut().zip(target.curls) {
+            lerp(value, target);
+        }
+    }
+}
+
+struct SmoothedAxes {
+    values: HandAxisValues,
+    /// Timestamp of the frame the values were last updated with, in microseconds.
+    timestamp: i64,
+}
+
+/// Smoothed analog values of the tracked hands, updated every frame.
+/// Useful for continuous controls, e.g. driving a brush size with pinch strength.
+#[derive(Resource)]
+pub struct HandAxes {
+    /// Time constant of the exponential smoothing; zero disables smoothing.
+    pub smoothing: Duration,
+    hands: HashMap<HandType, SmoothedAxes>,
+}
+
+impl HandAxes {
+    /// Returns values of the hand, or `None` if it is not tracked.
+    pub fn get(&self, hand_type: HandType) -> Option<&HandAxisValues> {
+        self.hands.get(&hand_type).map(|axes| &axes.values)
+    }
+}
+
+impl Default for HandAxes {
+    fn default() -> Self {
+        Self {
+            smoothing: Duration::from_millis(50),
+            hands: HashMap::default(),
+        }
+    }
+}
+
+/// Adds [`HandAxes`]; pinch strengths are calculated with [`PinchCalibration`].
+/// Added only once; listed as "hand_axes" in [`crate::gesture::RegisteredGestures`].
+pub fn add_hand_axes(app: &mut App) {
+    if !register_gesture_name(app, "hand_axes") {
+        return;
+    }
+
+    app.init_resource::<HandAxes>()
+        .init_resource::<PinchCalibration>()
+        .add_systems(Update, update_hand_axes.in_set(DetectGestures));
+}
+
+pub(crate) fn update_hand_axes(
+    hands_data: Res<HandsData>,
+    calibration: Res<PinchCalibration>,
+    mut hand_axes: ResMut<HandAxes>,
+) {
+    let smoothing = hand_axes.smoothing.as_secs_f32();
+
+    for hand_type in [HandType::Left, HandType::Right] {
+        let Some(sample) = hands_data
+            .history(hand_type)
+            .and_then(|history| history.latest_sample())
+        else {
+            hand_axes.hands.remove(&hand_type);
+            continue;
+        };
+
+        let target = HandAxisValues::from_hand(&sample.hand, &calibration);
+        match hand_axes.hands.get_mut(&hand_type) {
+            Some(axes) if smoothing > 0.0 => {
+                // frame rate independent exponential smoothing
+                let delta = (sample.timestamp - axes.timestamp).max(0) as f32 / 1_000_000.0;
+                axes.values.approach(&target, 1.0 - (-delta / smoothing).exp());
+                axes.timestamp = sample.timestamp;
+            }
+            _ => {
+                hand_axes.hands.insert(
+                    hand_type,
+                    SmoothedAxes {
+                        values: target,
+                        timestamp: sample.timestamp,
+                    },
+                );
+            }
+        }
+    }
+}
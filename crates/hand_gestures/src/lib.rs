@@ -1,66 +1,381 @@
+use std::time::Duration;
+
 use bevy::app::{App, Plugin, Update};
-use bevy::prelude::{Deref, Resource};
+use bevy::prelude::{resource_changed, IntoSystemConfigs, IntoSystemSetConfigs, Res, ResMut, Resource, SystemSet};
 use bevy::reflect::{Array, List};
-pub use ringbuf::{Rb, StaticRb};
+use bevy::utils::HashMap;
+pub use ringbuf::{HeapRb, Rb, StaticRb};
 
-use crate::models::{Gesture, HandData};
-use crate::pinch_gesture::{detect_pinch_event, PinchGesture, PinchGestureInfo};
+use crate::circle_gesture::CircleDetector;
+use crate::custom_gesture::add_custom_gestures;
+use crate::flick_gesture::FlickDetector;
+use crate::gesture::{add_gesture, Gesture};
+use crate::grab_gesture::GrabDetector;
+use crate::hand_axes::add_hand_axes;
+use crate::models::{HandData, HandType};
+use crate::pinch_gesture::add_pinch_gestures;
+use crate::point_gesture::PointDetector;
+use crate::source::{TrackingFrame, UpdateHandsData};
+use crate::static_pose::PoseDetector;
+use crate::swipe_gesture::SwipeDetector;
+use crate::tap_gesture::TapDetector;
 
+pub mod circle_gesture;
+pub mod custom_gesture;
 pub mod flick_gesture;
+pub mod gesture;
+pub mod grab_gesture;
+pub mod hand_axes;
 pub mod models;
+pub mod pinch_calibration;
 pub mod pinch_gesture;
+pub mod point_gesture;
+pub mod recording;
+pub mod replay;
+pub mod source;
+pub mod static_pose;
+pub mod swipe_gesture;
+pub mod tap_gesture;
+pub mod two_hand_gesture;
+
+/// Default number of frames kept in [`HandsData`].
+pub const DEFAULT_HANDS_DATA_HISTORY_SIZE: usize = 30;
+
+/// Set containing gesture detection systems. Runs after [`UpdateHandsData`].
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DetectGestures;
+
+/// Configuration of [`HandsData`]; changing it at runtime resizes the history, keeping the newest frames.
+#[derive(Resource, Debug, Clone)]
+pub struct HandsDataConfig {
+    /// Number of frames kept in the history, per hand and for whole frames.
+    pub history_capacity: usize,
+}
+
+impl Default for HandsDataConfig {
+    fn default() -> Self {
+        Self {
+            history_capacity: DEFAULT_HANDS_DATA_HISTORY_SIZE,
+        }
+    }
+}
 
-const HANDS_DATA_HISTORY_SIZE: usize = 30;
+pub struct GesturePlugin {
+    pub config: HandsDataConfig,
+    /// Functions adding the detected gestures to the app.
+    gestures: Vec<fn(&mut App)>,
+}
 
-pub struct GesturePlugin;
+impl Default for GesturePlugin {
+    /// Plugin detecting all the built-in gestures.
+    fn default() -> Self {
+        Self {
+            config: HandsDataConfig::default(),
+            gestures: vec![
+                add_pinch_gestures,
+                add_gesture::<FlickDetector>,
+                add_gesture::<GrabDetector>,
+                add_gesture::<PointDetector>,
+                add_hand_axes,
+                add_gesture::<PoseDetector>,
+                add_gesture::<SwipeDetector>,
+                add_gesture::<CircleDetector>,
+                add_gesture::<TapDetector>,
+                add_custom_gestures,
+            ],
+        }
+    }
+}
+
+impl GesturePlugin {
+    pub fn with_history_capacity(history_capacity: usize) -> Self {
+        Self {
+            config: HandsDataConfig { history_capacity },
+            ..Self::default()
+        }
+    }
+
+    /// Plugin which only collects [`HandsData`]; detected gestures are chosen with [`GesturePlugin::with_gesture`]
+    /// and [`GesturePlugin::with_gestures`].
+    pub fn empty() -> Self {
+        Self {
+            config: HandsDataConfig::default(),
+            gestures: Vec::new(),
+        }
+    }
+
+    pub fn with_gesture<G: Gesture>(mut self) -> Self {
+        self.gestures.push(add_gesture::<G>);
+        self
+    }
+
+    /// Adds gestures which are not implemented with [`Gesture`], e.g. [`pinch_gesture::add_pinch_gestures`].
+    pub fn with_gestures(mut self, add_gestures: fn(&mut App)) -> Self {
+        self.gestures.push(add_gestures);
+        self
+    }
+}
 
 impl Plugin for GesturePlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<PinchGesture>()
-            .insert_resource(HandsData::default())
-            .insert_resource(PinchGestureInfo::new())
-            .add_systems(Update, detect_pinch_event);
+        app.insert_resource(HandsData::with_capacity(self.config.history_capacity))
+            .insert_resource(self.config.clone())
+            .configure_sets(Update, DetectGestures.after(UpdateHandsData))
+            .add_systems(
+                Update,
+                apply_hands_data_config
+                    .run_if(resource_changed::<HandsDataConfig>)
+                    .before(UpdateHandsData),
+            );
+
+        for add_gestures in &self.gestures {
+            add_gestures(app);
+        }
     }
 }
 
 pub type TwoHandsData = [Option<HandData>; 2];
 
-#[derive(Resource, Deref)]
+/// Hand data with the frame it comes from.
+#[derive(Clone)]
+pub struct HandSample {
+    pub frame_id: i64,
+    /// Timestamp of the frame, in microseconds.
+    pub timestamp: i64,
+    pub hand: HandData,
+}
+
+/// History of a single tracked hand. A new history is started when the tracker reports
+/// a hand with different id, so histories of different hands are never mixed.
+pub struct HandHistory {
+    id: Option<u32>,
+    samples: HeapRb<HandSample>,
+}
+
+impl HandHistory {
+    fn new(id: Option<u32>, capacity: usize) -> Self {
+        Self {
+            id,
+            samples: HeapRb::new(capacity),
+        }
+    }
+
+    /// Identifier assigned to the hand by the tracker.
+    pub fn id(&self) -> Option<u32> {
+        self.id
+    }
+
+    /// Returns hand data from the oldest to the newest frame.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &HandData> {
+        self.samples.iter().map(|sample| &sample.hand)
+    }
+
+    /// Returns samples from the oldest to the newest frame.
+    pub fn samples(&self) -> impl DoubleEndedIterator<Item = &HandSample> {
+        self.samples.iter()
+    }
+
+    pub fn latest(&self) -> Option<&HandData> {
+        self.latest_sample().map(|sample| &sample.hand)
+    }
+
+    pub fn latest_sample(&self) -> Option<&HandSample> {
+        self.samples.iter().next_back()
+    }
+
+    /// Returns samples newer than `timestamp`, oldest first. They are searched from the end of the history,
+    /// so the cost does not depend on its length.
+    pub fn samples_after(&self, timestamp: i64) -> impl Iterator<Item = &HandSample> {
+        let count = self
+            .samples
+            .iter()
+            .rev()
+            .take_while(|sample| sample.timestamp > timestamp)
+            .count();
+
+        self.samples.iter().skip(self.samples.len() - count)
+    }
+
+    /// Returns samples captured at most `window` before the newest one, oldest first.
+    pub fn samples_within(&self, window: Duration) -> impl Iterator<Item = &HandSample> {
+        let start = self
+            .latest_sample()
+            .map_or(i64::MAX, |sample| sample.timestamp - as_micros(window));
+
+        self.samples.iter().skip_while(move |sample| sample.timestamp < start)
+    }
+
+    /// Returns the hand at `timestamp`, interpolated between the closest samples,
+    /// or `None` if `timestamp` is outside of the history.
+    pub fn at(&self, timestamp: i64) -> Option<HandData> {
+        let (before, after) = self
+            .samples
+            .iter()
+            .zip(self.samples.iter().skip(1))
+            .find(|(_, after)| after.timestamp >= timestamp)
+            .or_else(|| self.latest_sample().map(|sample| (sample, sample)))?;
+
+        if timestamp < before.timestamp || timestamp > after.timestamp {
+            return None;
+        }
+
+        let span = after.timestamp - before.timestamp;
+        let t = if span > 0 {
+            (timestamp - before.timestamp) as f32 / span as f32
+        } else {
+            0.0
+        };
+
+        Some(before.hand.lerp(&after.hand, t))
+    }
+}
+
+/// Recent tracking frames and histories of the tracked hands. Frames are added only with
+/// [`HandsData::push_overwrite`], which keeps the histories in sync with them.
+#[derive(Resource)]
 pub struct HandsData {
-    pub historical_data: StaticRb<TwoHandsData, HANDS_DATA_HISTORY_SIZE>,
+    historical_data: HeapRb<TrackingFrame>,
+    /// Histories of the currently tracked hands.
+    hands: HashMap<HandType, HandHistory>,
+    pushed_frames: u64,
 }
 
 impl HandsData {
-    pub fn push_overwrite(&mut self, elem: [Option<HandData>; 2]) -> Option<[Option<HandData>; 2]> {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            historical_data: HeapRb::new(capacity.max(1)),
+            hands: HashMap::default(),
+            pushed_frames: 0,
+        }
+    }
+
+    /// Changes the number of kept frames; the newest frames are preserved.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        let capacity = capacity.max(1);
+        if capacity == self.historical_data.capacity() {
+            return;
+        }
+
+        self.historical_data = resized(&mut self.historical_data, capacity);
+        for history in self.hands.values_mut() {
+            history.samples = resized(&mut history.samples, capacity);
+        }
+    }
+
+    pub fn push_overwrite(&mut self, elem: TrackingFrame) -> Option<TrackingFrame> {
+        for hand_type in [HandType::Left, HandType::Right] {
+            // if a tracker reports two hands of the same chirality, the first one is used
+            let Some(hand) = elem.hands.iter().flatten().find(|hand| hand.type_ == hand_type) else {
+                self.hands.remove(&hand_type);
+                continue;
+            };
+
+            let capacity = self.historical_data.capacity();
+            let history = self
+                .hands
+                .entry(hand_type)
+                .or_insert_with(|| HandHistory::new(hand.id, capacity));
+            if history.id != hand.id {
+                *history = HandHistory::new(hand.id, capacity);
+            }
+            history.samples.push_overwrite(HandSample {
+                frame_id: elem.frame_id,
+                timestamp: elem.timestamp,
+                hand: hand.clone(),
+            });
+        }
+
+        self.pushed_frames += 1;
         self.historical_data.push_overwrite(elem)
     }
 
-    pub fn get_iters(&self) -> (impl Iterator<Item = &HandData>, impl Iterator<Item = &HandData>) {
-        let fist_hand_iter = self.get_hand_iter(0);
-        let second_hand_iter = self.get_hand_iter(1);
+    /// Removes all frames and hand histories, e.g. when the source jumps in time.
+    /// Frames pushed afterwards start new histories, so the jump is not seen as a movement of the hands.
+    pub fn clear(&mut self) {
+        self.historical_data.clear();
+        self.hands.clear();
+    }
 
-        (fist_hand_iter, second_hand_iter)
+    /// Returns the kept frames, from the oldest to the newest.
+    pub fn frames(&self) -> impl DoubleEndedIterator<Item = &TrackingFrame> {
+        self.historical_data.iter()
     }
 
-    fn get_hand_iter(&self, hand_index: usize) -> impl Iterator<Item = &HandData> {
+    /// Number of frames pushed since the creation, including ones already overwritten.
+    pub fn pushed_frames(&self) -> u64 {
+        self.pushed_frames
+    }
+
+    /// Returns history of the hand, if it is present in the newest frame.
+    pub fn history(&self, hand_type: HandType) -> Option<&HandHistory> {
+        self.hands.get(&hand_type)
+    }
+
+    /// Returns data of the hand from the oldest to the newest frame;
+    /// empty if the hand is not tracked at the moment.
+    pub fn hand_history(&self, hand_type: HandType) -> impl DoubleEndedIterator<Item = &HandData> {
+        self.history(hand_type).into_iter().flat_map(HandHistory::iter)
+    }
+
+    /// Returns data of the hand captured at most `window` before the newest frame, oldest first.
+    pub fn hand_samples_within(&self, hand_type: HandType, window: Duration) -> impl Iterator<Item = &HandSample> {
+        self.history(hand_type)
+            .into_iter()
+            .flat_map(move |history| history.samples_within(window))
+    }
+
+    /// Returns the hand at `timestamp`, interpolated between the closest frames.
+    pub fn hand_at(&self, hand_type: HandType, timestamp: i64) -> Option<HandData> {
+        self.history(hand_type)?.at(timestamp)
+    }
+
+    /// Timestamp of the newest frame, in microseconds.
+    pub fn latest_timestamp(&self) -> Option<i64> {
+        self.historical_data.iter().next_back().map(|frame| frame.timestamp)
+    }
+
+    /// Returns frames captured at most `window` before the newest one, oldest first.
+    pub fn frames_within(&self, window: Duration) -> impl Iterator<Item = &TrackingFrame> {
+        let start = self
+            .latest_timestamp()
+            .map_or(i64::MAX, |timestamp| timestamp - as_micros(window));
+
         self.historical_data
             .iter()
-            .map(move |x| x[hand_index].as_ref())
-            .take_while(Option::is_some)
-            .map(Option::unwrap)
+            .skip_while(move |frame| frame.timestamp < start)
+    }
+
+    /// Returns histories of the left and the right hand, see [`HandsData::hand_history`].
+    pub fn get_iters(&self) -> (impl Iterator<Item = &HandData>, impl Iterator<Item = &HandData>) {
+        (self.hand_history(HandType::Left), self.hand_history(HandType::Right))
+    }
+}
+
+pub(crate) fn as_micros(duration: Duration) -> i64 {
+    duration.as_micros().min(i64::MAX as u128) as i64
+}
+
+fn resized<T>(rb: &mut HeapRb<T>, capacity: usize) -> HeapRb<T> {
+    let mut resized = HeapRb::new(capacity);
+    for item in rb.pop_iter() {
+        resized.push_overwrite(item);
     }
+    resized
 }
 
 impl Default for HandsData {
     fn default() -> Self {
-        Self {
-            historical_data: StaticRb::<[Option<HandData>; 2], HANDS_DATA_HISTORY_SIZE>::default(),
-        }
+        Self::with_capacity(DEFAULT_HANDS_DATA_HISTORY_SIZE)
     }
 }
 
+fn apply_hands_data_config(config: Res<HandsDataConfig>, mut hands_data: ResMut<HandsData>) {
+    hands_data.set_capacity(config.history_capacity);
+}
+
 struct GestureOccurrenceInfo {
-    gesture: Box<dyn Gesture>,
+    /// see [`Gesture::NAME`]
+    gesture: &'static str,
     real_time: usize,
     game_time: usize,
 }
@@ -74,7 +389,5 @@ struct GestureManager {
 }
 
 impl GestureManager {
-    fn save_gestures(&self) {
-
-    }
+    fn save_gestures(&self) {}
 }
@@ -1,7 +1,5 @@
 use bevy::math::Vec3;
 
-pub trait Gesture {}
-
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum HandType {
     Left,
@@ -10,6 +8,8 @@ pub enum HandType {
 
 #[derive(Clone)]
 pub struct HandData {
+    /// Identifier assigned by the tracker, which stays the same while the hand is tracked.
+    pub id: Option<u32>,
     /// Identifies the chirality of this hand.
     pub type_: HandType,
     /// How confident we are with a given hand pose.
@@ -19,6 +19,134 @@ pub struct HandData {
     pub middle: Finger,
     pub ring: Finger,
     pub pinky: Finger,
+    pub palm: Option<Palm>,
+    pub arm: Option<Arm>,
+    /// How strongly the hand is closed into a fist, from 0 to 1.
+    pub grab_strength: Option<f32>,
+    /// How strongly the thumb is pinched with any other finger, from 0 to 1.
+    pub pinch_strength: Option<f32>,
+}
+
+impl HandData {
+    /// Interpolates joints and values of two poses of the same hand; `t` = 0 returns `self`.
+    /// Optional values are interpolated only if both hands have them.
+    pub fn lerp(&self, other: &HandData, t: f32) -> HandData {
+        let lerp_finger = |from: &Finger, to: &Finger| {
+            let mut finger = *from;
+            for (joint, target) in finger.iter_mut().zip(to) {
+                *joint = joint.lerp(*target, t);
+            }
+            finger
+        };
+        let lerp_value = |from: f32, to: f32| from + (to - from) * t;
+
+        HandData {
+            id: self.id,
+            type_: self.type_,
+            confidence: lerp_value(self.confidence, other.confidence),
+            thumb: lerp_finger(&self.thumb, &other.thumb),
+            index: lerp_finger(&self.index, &other.index),
+            middle: lerp_finger(&self.middle, &other.middle),
+            ring: lerp_finger(&self.ring, &other.ring),
+            pinky: lerp_finger(&self.pinky, &other.pinky),
+            palm: self.palm.zip(other.palm).map(|(from, to)| Palm {
+                position: from.position.lerp(to.position, t),
+                normal: from.normal.lerp(to.normal, t).normalize_or_zero(),
+                direction: from.direction.lerp(to.direction, t).normalize_or_zero(),
+                velocity: from.velocity.lerp(to.velocity, t),
+                width: lerp_value(from.width, to.width),
+            }),
+            arm: self.arm.zip(other.arm).map(|(from, to)| Arm {
+                wrist: from.wrist.lerp(to.wrist, t),
+                elbow: from.elbow.lerp(to.elbow, t),
+                width: lerp_value(from.width, to.width),
+            }),
+            grab_strength: self
+                .grab_strength
+                .zip(other.grab_strength)
+                .map(|(from, to)| lerp_value(from, to)),
+            pinch_strength: self
+                .pinch_strength
+                .zip(other.pinch_strength)
+                .map(|(from, to)| lerp_value(from, to)),
+        }
+    }
+
+    /// Returns index, middle, ring and pinky fingers.
+    pub fn fingers(&self) -> [&Finger; 4] {
+        [&self.index, &self.middle, &self.ring, &self.pinky]
+    }
+
+    /// Center of the palm; approximated from the finger joints if the tracker does not provide it.
+    pub fn palm_center(&self) -> Vec3 {
+        match self.palm {
+            Some(palm) => palm.position,
+            None => self.fingers().iter().map(|finger| finger[3] + finger[4]).sum::<Vec3>() / 8.0,
+        }
+    }
+
+    /// Length of the index proximal bone; used to express distances independently of the hand size
+    /// and units of the tracker.
+    pub fn size(&self) -> f32 {
+        self.index[2].distance(self.index[3])
+    }
+
+    /// Returns how much the four fingers are curled towards the palm, from 0 (open hand) to 1 (fist).
+    pub fn grab_curl(&self) -> f32 {
+        let palm_center = self.palm_center();
+        self.fingers()
+            .iter()
+            .map(|finger| finger_curl(finger, palm_center))
+            .sum::<f32>()
+            / 4.0
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Palm {
+    /// Center of the palm.
+    pub position: Vec3,
+    /// Unit vector pointing out of the palm.
+    pub normal: Vec3,
+    /// Unit vector pointing from the palm towards fingers.
+    pub direction: Vec3,
+    /// Velocity of the palm position, in units per second.
+    pub velocity: Vec3,
+    pub width: f32,
 }
 
+#[derive(Copy, Clone, Debug)]
+pub struct Arm {
+    pub wrist: Vec3,
+    pub elbow: Vec3,
+    pub width: f32,
+}
+
+/// Joints of a finger, from the tip to the base of the metacarpal bone.
 pub type Finger = [Vec3; 5];
+
+/// Returns how straight the finger is, from around 0.3 for a fully curled finger to 1 for a straight one.
+/// Calculated as the distance between the tip and the knuckle, relative to the length of phalanges.
+pub fn finger_extension(finger: &Finger) -> f32 {
+    let phalanges_length: f32 = finger[..4].windows(2).map(|joints| joints[0].distance(joints[1])).sum();
+    if phalanges_length <= f32::EPSILON {
+        return 1.0;
+    }
+
+    finger[0].distance(finger[3]) / phalanges_length
+}
+
+/// Returns how much the finger is curled towards `palm_center`, from 0 (straight) to 1 (touching the palm).
+/// Calculated from the distance between the tip and the palm center, relative to the length of the finger.
+pub fn finger_curl(finger: &Finger, palm_center: Vec3) -> f32 {
+    const STRAIGHT_TIP_DISTANCE: f32 = 0.9;
+    const CURLED_TIP_DISTANCE: f32 = 0.4;
+
+    let finger_length: f32 = finger.windows(2).map(|joints| joints[0].distance(joints[1])).sum();
+    if finger_length <= f32::EPSILON {
+        return 0.0;
+    }
+
+    let tip_distance = finger[0].distance(palm_center) / finger_length;
+    ((STRAIGHT_TIP_DISTANCE - tip_distance) / (STRAIGHT_TIP_DISTANCE - CURLED_TIP_DISTANCE)).clamp(0.0, 1.0)
+}
@@ -0,0 +1,321 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use bevy::log::warn;
+use bevy::math::Vec3;
+use bevy::prelude::{Res, ResMut, Resource};
+
+use crate::models::{HandData, HandType};
+use crate::{as_micros, HandsData};
+
+/// Time for which distances are measured in every calibration stage.
+const CALIBRATION_STAGE_DURATION: Duration = Duration::from_secs(2);
+/// Smallest accepted ratio of open and pinched distances; lower values mean the user did not follow the stages.
+const MIN_CALIBRATION_RANGE_RATIO: f32 = 1.5;
+
+/// Distances between tips of the thumb and a finger, relative to the hand size (see [`relative_pinch_distance`]).
+/// Used to calculate pinch strength of every finger, see [`crate::pinch_gesture::pinch_strength`].
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct PinchCalibration {
+    /// Relative distance of touching finger tips.
+    pub pinched_distance: f32,
+    /// Relative distance of finger tips of an open hand.
+    pub open_distance: f32,
+    /// Set when the distances were measured by [`PinchCalibrator`].
+    pub is_user_calibrated: bool,
+    /// Use the pinch strength reported by the tracker, if any, in place of the one calculated from distances.
+    /// The tracker value is not specific to a finger, and not affected by the calibration.
+    pub use_tracker_strength: bool,
+}
+
+impl PinchCalibration {
+    /// Returns pinch strength from 0 (open hand) to 1 (touching tips) for a relative distance.
+    pub fn normalize(&self, relative_distance: f32) -> f32 {
+        let range = self.open_distance - self.pinched_distance;
+        if range <= f32::EPSILON {
+            return 0.0;
+        }
+
+        ((self.open_distance - relative_distance) / range).clamp(0.0, 1.0)
+    }
+}
+
+impl Default for PinchCalibration {
+    fn default() -> Self {
+        Self {
+            pinched_distance: 0.4,
+            open_distance: 1.75,
+            is_user_calibrated: false,
+            use_tracker_strength: false,
+        }
+    }
+}
+
+/// Distance between the thumb tip and `finger_tip`, divided by the length of the index proximal bone,
+/// so it does not depend on the hand size nor the units of the tracker.
+pub fn relative_pinch_distance(hand: &HandData, finger_tip: Vec3) -> Option<f32> {
+    let hand_size = hand.size();
+    if hand_size <= f32::EPSILON {
+        return None;
+    }
+
+    Some(finger_tip.distance(hand.thumb[0]) / hand_size)
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum CalibrationStage {
+    #[default]
+    Idle,
+    /// The user should keep the hand open.
+    OpenHand,
+    /// The user should pinch the thumb and the index finger.
+    Pinch,
+}
+
+/// Reason why [`PinchCalibrator`] left [`PinchCalibration`] unchanged.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CalibrationError {
+    /// No distance was measured in a stage, as the hand size could not be measured.
+    NoMeasurements,
+    /// Open hand and pinch distances are too similar; the user probably did not follow the stages.
+    DistancesTooSimilar,
+}
+
+impl Display for CalibrationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalibrationError::NoMeasurements => write!(f, "no distances were measured"),
+            CalibrationError::DistancesTooSimilar => write!(f, "open hand and pinch distances are too similar"),
+        }
+    }
+}
+
+impl std::error::Error for CalibrationError {}
+
+/// Measures distances of the user's open hand and pinch, and stores them in [`PinchCalibration`].
+/// Every stage lasts [`CALIBRATION_STAGE_DURATION`] of tracking time, counted from the first frame with a hand.
+#[derive(Resource, Default)]
+pub struct PinchCalibrator {
+    stage: CalibrationStage,
+    stage_started_at: Option<i64>,
+    last_timestamp: Option<i64>,
+    open_distances: Vec<f32>,
+    pinched_distances: Vec<f32>,
+    /// Error of the last finished calibration, cleared when a new one starts.
+    error: Option<CalibrationError>,
+}
+
+impl PinchCalibrator {
+    pub fn start(&mut self) {
+        *self = Self {
+            stage: CalibrationStage::OpenHand,
+            ..Self::default()
+        };
+    }
+
+    pub fn cancel(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn stage(&self) -> CalibrationStage {
+        self.stage
+    }
+
+    /// Returns why the last calibration failed, or `None` if it succeeded or none was finished yet.
+    pub fn error(&self) -> Option<CalibrationError> {
+        self.error
+    }
+}
+
+pub(crate) fn calibrate_pinch(
+    hands_data: Res<HandsData>,
+    mut calibrator: ResMut<PinchCalibrator>,
+    mut calibration: ResMut<PinchCalibration>,
+) {
+    if calibrator.stage == CalibrationStage::Idle {
+        return;
+    }
+
+    // the right hand is preferred, if both are tracked
+    let Some(sample) = [HandType::Right, HandType::Left]
+        .into_iter()
+        .find_map(|hand_type| hands_data.history(hand_type)?.latest_sample())
+    else {
+        return;
+    };
+    if calibrator.last_timestamp == Some(sample.timestamp) {
+        return;
+    }
+    calibrator.last_timestamp = Some(sample.timestamp);
+
+    let stage_started_at = *calibrator.stage_started_at.get_or_insert(sample.timestamp);
+    if let Some(distance) = relative_pinch_distance(&sample.hand, sample.hand.index[0]) {
+        match calibrator.stage {
+            CalibrationStage::OpenHand => calibrator.open_distances.push(distance),
+            CalibrationStage::Pinch => calibrator.pinched_distances.push(distance),
+            CalibrationStage::Idle => {}
+        }
+    }
+
+    if sample.timestamp - stage_started_at < as_micros(CALIBRATION_STAGE_DURATION) {
+        return;
+    }
+
+    match calibrator.stage {
+        CalibrationStage::OpenHand => {
+            calibrator.stage = CalibrationStage::Pinch;
+            calibrator.stage_started_at = None;
+        }
+        CalibrationStage::Pinch => {
+            let open_distance = median(&mut calibrator.open_distances);
+            let pinched_distance = median(&mut calibrator.pinched_distances);
+            let error = match (open_distance, pinched_distance) {
+                (Some(open_distance), Some(pinched_distance))
+                    if open_distance >= pinched_distance * MIN_CALIBRATION_RANGE_RATIO =>
+                {
+                    *calibration = PinchCalibration {
+                        pinched_distance,
+                        open_distance,
+                        is_user_calibrated: true,
+                        ..*calibration
+                    };
+                    None
+                }
+                (Some(_), Some(_)) => Some(CalibrationError::DistancesTooSimilar),
+                _ => Some(CalibrationError::NoMeasurements),
+            };
+            if let Some(error) = error {
+                warn!("pinch calibration failed: {error}");
+            }
+            calibrator.cancel();
+            calibrator.error = error;
+        }
+        CalibrationStage::Idle => {}
+    }
+}
+
+fn median(values: &mut [f32]) -> Option<f32> {
+    values.sort_unstable_by(f32::total_cmp);
+    values.get(values.len() / 2).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::app::App;
+
+    use super::*;
+    use crate::pinch_gesture::add_pinch_gestures;
+    use crate::source::TrackingFrame;
+    use crate::GesturePlugin;
+
+    /// Time between frames, in microseconds.
+    const FRAME_INTERVAL: i64 = 10_000;
+
+    /// Right hand with the index proximal bone 40 long and the thumb tip `relative_distance` hand sizes
+    /// from the index tip.
+    fn hand(relative_distance: f32) -> HandData {
+        let finger = [
+            Vec3::new(0.0, 0.0, -80.0),
+            Vec3::new(0.0, 0.0, -60.0),
+            Vec3::new(0.0, 0.0, -40.0),
+            Vec3::ZERO,
+            Vec3::new(0.0, 0.0, 60.0),
+        ];
+        let mut thumb = finger.map(|joint| joint + Vec3::X * 30.0);
+        thumb[0] = finger[0] + Vec3::X * relative_distance * 40.0;
+        HandData {
+            id: Some(1),
+            type_: HandType::Right,
+            confidence: 1.0,
+            thumb,
+            index: finger,
+            middle: finger,
+            ring: finger,
+            pinky: finger,
+            palm: None,
+            arm: None,
+            grab_strength: None,
+            pinch_strength: None,
+        }
+    }
+
+    /// Runs a calibration in which the hand keeps `open_distance` in the open hand stage and `pinched_distance`
+    /// in the pinch stage.
+    fn calibrate(open_distance: f32, pinched_distance: f32) -> App {
+        let mut app = App::new();
+        app.add_plugins(GesturePlugin::empty().with_gestures(add_pinch_gestures));
+        app.world.resource_mut::<PinchCalibrator>().start();
+
+        let stage_frames = as_micros(CALIBRATION_STAGE_DURATION) / FRAME_INTERVAL + 1;
+        for frame in 0..2 * stage_frames {
+            let distance = if frame < stage_frames {
+                open_distance
+            } else {
+                pinched_distance
+            };
+            app.world.resource_mut::<HandsData>().push_overwrite(TrackingFrame {
+                frame_id: frame,
+                timestamp: frame * FRAME_INTERVAL,
+                hands: [Some(hand(distance)), None],
+            });
+            app.update();
+        }
+        app
+    }
+
+    #[test]
+    fn calibrated_distances_are_normalized_to_strength() {
+        let calibration = PinchCalibration {
+            pinched_distance: 0.5,
+            open_distance: 2.5,
+            ..PinchCalibration::default()
+        };
+
+        assert_eq!(calibration.normalize(0.5), 1.0);
+        assert_eq!(calibration.normalize(2.5), 0.0);
+        assert_eq!(calibration.normalize(1.5), 0.5);
+        assert_eq!(calibration.normalize(0.0), 1.0);
+        assert_eq!(calibration.normalize(4.0), 0.0);
+    }
+
+    #[test]
+    fn empty_range_is_normalized_to_zero() {
+        let calibration = PinchCalibration {
+            pinched_distance: 1.0,
+            open_distance: 1.0,
+            ..PinchCalibration::default()
+        };
+
+        assert_eq!(calibration.normalize(0.5), 0.0);
+    }
+
+    #[test]
+    fn relative_distance_does_not_depend_on_hand_size() {
+        assert!((relative_pinch_distance(&hand(1.5), hand(1.5).index[0]).unwrap() - 1.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn calibration_stores_measured_distances() {
+        let app = calibrate(2.0, 0.25);
+
+        let calibration = app.world.resource::<PinchCalibration>();
+        assert!(calibration.is_user_calibrated);
+        assert!((calibration.open_distance - 2.0).abs() < 1e-5);
+        assert!((calibration.pinched_distance - 0.25).abs() < 1e-5);
+        let calibrator = app.world.resource::<PinchCalibrator>();
+        assert_eq!(calibrator.stage(), CalibrationStage::Idle);
+        assert_eq!(calibrator.error(), None);
+    }
+
+    #[test]
+    fn failed_calibration_is_reported_and_keeps_previous_values() {
+        let app = calibrate(1.0, 0.9);
+
+        let calibration = app.world.resource::<PinchCalibration>();
+        assert!(!calibration.is_user_calibrated);
+        assert_eq!(calibration.open_distance, PinchCalibration::default().open_distance);
+        let calibrator = app.world.resource::<PinchCalibrator>();
+        assert_eq!(calibrator.stage(), CalibrationStage::Idle);
+        assert_eq!(calibrator.error(), Some(CalibrationError::DistancesTooSimilar));
+    }
+}
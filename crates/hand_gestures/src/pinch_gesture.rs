@@ -1,125 +1,482 @@
+use std::time::Duration;
+
+use bevy::app::{App, Update};
 use bevy::math::Vec3;
-use bevy::prelude::{Event, EventWriter, Real, Res, ResMut, Resource, Time, Transform};
+use bevy::prelude::{Event, EventReader, EventWriter, IntoSystemConfigs, Res, ResMut, Resource, Transform};
 use bevy::utils::HashMap;
 
-use crate::HandsData;
-use crate::models::{Gesture, HandData, HandType};
+use crate::gesture::register_gesture_name;
+use crate::models::{Finger, HandData, HandType};
+use crate::pinch_calibration::{calibrate_pinch, relative_pinch_distance, PinchCalibration, PinchCalibrator};
+use crate::two_hand_gesture::{
+    detect_two_hand_pinch, TwoHandPinch, TwoHandPinchEnded, TwoHandPinchInfo, TwoHandPinchStarted,
+};
+use crate::{as_micros, DetectGestures, HandsData};
+
+/// Longest pinch which is still reported as [`PinchGesture`].
+const PINCH_TAP_MAX_DURATION: Duration = Duration::from_millis(500);
+/// Smallest move of the pinch point reported as [`PinchMoved`], relative to the hand size
+/// (see [`HandData::size`]); around 0.5 mm for an average hand.
+const PINCH_MOVE_MIN_DISTANCE: f32 = 0.0125;
+
+/// Hysteresis of [`PinchDetector`], applied to the strength from [`pinch_strength`] of every finger.
+/// Read every update, so the thresholds can be tuned while the app runs.
+#[derive(Resource, Debug, Clone)]
+pub struct PinchConfig {
+    /// Pinch strength above which a pinch starts.
+    pub engage_threshold: f32,
+    /// Pinch strength below which a pinch ends. Lower than `engage_threshold`,
+    /// so noise around a single threshold does not split one pinch into many.
+    pub release_threshold: f32,
+    /// Time the strength has to stay above `engage_threshold` before the pinch is reported;
+    /// filters out single noisy frames.
+    pub min_hold: Duration,
+}
+
+impl Default for PinchConfig {
+    fn default() -> Self {
+        Self {
+            engage_threshold: 0.7,
+            release_threshold: 0.5,
+            min_hold: Duration::from_millis(30),
+        }
+    }
+}
+
+/// Finger touching the thumb in a pinch.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PinchFinger {
+    Index,
+    Middle,
+    Ring,
+    Pinky,
+}
+
+impl PinchFinger {
+    pub const ALL: [PinchFinger; 4] = [
+        PinchFinger::Index,
+        PinchFinger::Middle,
+        PinchFinger::Ring,
+        PinchFinger::Pinky,
+    ];
+
+    pub fn joints(self, hand: &HandData) -> &Finger {
+        match self {
+            PinchFinger::Index => &hand.index,
+            PinchFinger::Middle => &hand.middle,
+            PinchFinger::Ring => &hand.ring,
+            PinchFinger::Pinky => &hand.pinky,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PinchTransition {
+    Started,
+    Released,
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+enum PinchState {
+    #[default]
+    Released,
+    /// Strength exceeded the engage threshold, but not for [`PinchConfig::min_hold`] yet.
+    Engaging {
+        since: i64,
+    },
+    Pinched,
+}
 
-const PINCH_GESTURE_MIN_INTERVAL: f32 = 0.5;
-const PINCH_DISTANCE_THRESHOLD: f32 = 0.7;
-// TODO: make this input agnostic; these values come are related to LeapC
-const MIN_PINCH_DISTANCE: f32 = 15.0;
-const MAX_PINCH_DISTANCE: f32 = 70.0;
+/// Incremental pinch state machine of a single finger, updated once per tracking frame.
+#[derive(Debug, Default, Clone)]
+pub struct PinchDetector {
+    state: PinchState,
+}
 
-#[derive(Resource)]
+impl PinchDetector {
+    /// Updates the state with pinch strength of a new frame; `timestamp` is in microseconds.
+    pub fn update(&mut self, config: &PinchConfig, timestamp: i64, strength: f32) -> Option<PinchTransition> {
+        match self.state {
+            PinchState::Released if strength > config.engage_threshold => {
+                self.state = PinchState::Engaging { since: timestamp };
+            }
+            // nothing was reported yet, so a dip below the engage threshold cancels the pinch silently
+            PinchState::Engaging { .. } if strength <= config.engage_threshold => {
+                self.state = PinchState::Released;
+            }
+            PinchState::Pinched if strength < config.release_threshold => {
+                return self.release();
+            }
+            _ => {}
+        }
+
+        match self.state {
+            PinchState::Engaging { since } if timestamp - since >= as_micros(config.min_hold) => {
+                self.state = PinchState::Pinched;
+                Some(PinchTransition::Started)
+            }
+            _ => None,
+        }
+    }
+
+    /// Ends the pinch, e.g. when the hand is lost by the tracker.
+    pub fn release(&mut self) -> Option<PinchTransition> {
+        let was_pinched = matches!(self.state, PinchState::Pinched);
+        self.state = PinchState::Released;
+
+        was_pinched.then_some(PinchTransition::Released)
+    }
+
+    pub fn is_pinching(&self) -> bool {
+        matches!(self.state, PinchState::Pinched)
+    }
+}
+
+struct ActivePinch {
+    /// Timestamp of the frame in which the pinch started, in microseconds.
+    started_at: i64,
+    /// Transform in the newest frame.
+    transform: Transform,
+    /// Translation reported by the last [`PinchMoved`], or at the start of the pinch.
+    last_moved: Vec3,
+}
+
+#[derive(Default)]
+struct FingerPinchState {
+    detector: PinchDetector,
+    active_pinch: Option<ActivePinch>,
+    /// Timestamp of the newest frame passed to the detector.
+    last_timestamp: Option<i64>,
+}
+
+#[derive(Resource, Default)]
 pub struct PinchGestureInfo {
-    last_pinch_times: HashMap<HandType, f32>,
+    fingers: HashMap<(HandType, PinchFinger), FingerPinchState>,
 }
 
 impl PinchGestureInfo {
     pub(crate) fn new() -> Self {
         Self::default()
     }
-}
 
-impl Default for PinchGestureInfo {
-    fn default() -> Self {
-        Self {
-            last_pinch_times: HashMap::from([(HandType::Right, -1f32), (HandType::Left, -1f32)])
-        }
+    pub fn is_pinching(&self, hand_type: HandType, finger: PinchFinger) -> bool {
+        self.fingers
+            .get(&(hand_type, finger))
+            .is_some_and(|state| state.active_pinch.is_some())
+    }
+
+    /// Returns the current transform of the pinch, or `None` if the finger is not pinching.
+    pub fn pinch_transform(&self, hand_type: HandType, finger: PinchFinger) -> Option<Transform> {
+        let state = self.fingers.get(&(hand_type, finger))?;
+        state.active_pinch.as_ref().map(|pinch| pinch.transform)
     }
 }
 
+/// Short pinch, i.e. a pinch which was released before [`PINCH_TAP_MAX_DURATION`].
 #[derive(Event, Debug, Clone)]
 pub struct PinchGesture {
     pub hand_type: HandType,
-    /// the point between an index finger and a thumb
+    pub finger: PinchFinger,
+    /// the point between the pinching finger and a thumb
     pub transform: Transform,
 }
 
-impl Gesture for PinchGesture {}
+#[derive(Event, Debug, Clone)]
+pub struct PinchStarted {
+    pub hand_type: HandType,
+    pub finger: PinchFinger,
+    pub transform: Transform,
+}
 
-enum Stage {
-    BeforePinch(usize),
-    Pinching(usize),
-    AfterPinch(usize),
+/// Sent once per app update while the hand is pinching, with the newest transform.
+#[derive(Event, Debug, Clone)]
+pub struct PinchHeld {
+    pub hand_type: HandType,
+    pub finger: PinchFinger,
+    pub transform: Transform,
+    /// time elapsed since the pinch started
+    pub duration: Duration,
 }
 
-/// Finding 'pinch gesture', by checking if hands history data contains
-/// sequence of pinch_strength values which starts and ends below threshold, but reaches
-/// threshold in between
-/// Example value for pinch gestures, threshold: 0.7 (newest -> oldest):
-/// [0.1, 0.1, 0.3, 0.5, 0.8, 0.7, 0.4, 0.2, 0.3, 0.2, 0.1]
-pub fn detect_pinch_event(
+/// Sent when the point of a held pinch moves. Every tracking frame is checked, so several can be sent
+/// in one app update.
+#[derive(Event, Debug, Clone)]
+pub struct PinchMoved {
+    pub hand_type: HandType,
+    pub finger: PinchFinger,
+    pub transform: Transform,
+    /// translation since the previous [`PinchMoved`] of this pinch, or since it started
+    pub delta: Vec3,
+}
+
+/// Sent when the pinch ends or the hand is lost by the tracker.
+#[derive(Event, Debug, Clone)]
+pub struct PinchReleased {
+    pub hand_type: HandType,
+    pub finger: PinchFinger,
+    /// the last known pinch transform
+    pub transform: Transform,
+    pub duration: Duration,
+}
+
+/// Adds pinch events of every finger, pinch calibration and the two-hand pinch, which is built on top of them.
+/// Added only once; listed as "pinch" in [`crate::gesture::RegisteredGestures`].
+pub fn add_pinch_gestures(app: &mut App) {
+    if !register_gesture_name(app, "pinch") {
+        return;
+    }
+
+    app.add_event::<PinchGesture>()
+        .add_event::<PinchStarted>()
+        .add_event::<PinchHeld>()
+        .add_event::<PinchMoved>()
+        .add_event::<PinchReleased>()
+        .add_event::<TwoHandPinchStarted>()
+        .add_event::<TwoHandPinch>()
+        .add_event::<TwoHandPinchEnded>()
+        .insert_resource(PinchGestureInfo::new())
+        .init_resource::<PinchConfig>()
+        .init_resource::<PinchCalibration>()
+        .init_resource::<PinchCalibrator>()
+        .insert_resource(TwoHandPinchInfo::new())
+        .add_systems(
+            Update,
+            (
+                calibrate_pinch,
+                update_pinch_state,
+                (detect_pinch_event, detect_two_hand_pinch),
+            )
+                .chain()
+                .in_set(DetectGestures),
+        );
+}
+
+/// Tracks pinch of every finger of both hands with a [`PinchDetector`]. Every frame is passed to the detector once,
+/// also if several frames arrived since the last update.
+#[allow(clippy::too_many_arguments)]
+pub fn update_pinch_state(
     hands_data: Res<HandsData>,
+    config: Res<PinchConfig>,
+    calibration: Res<PinchCalibration>,
     mut pinch_gesture_info: ResMut<PinchGestureInfo>,
-    mut hand_pinch: EventWriter<PinchGesture>,
-    time: Res<Time<Real>>,
+    mut pinch_started: EventWriter<PinchStarted>,
+    mut pinch_held: EventWriter<PinchHeld>,
+    mut pinch_moved: EventWriter<PinchMoved>,
+    mut pinch_released: EventWriter<PinchReleased>,
 ) {
-    // TODO: consider if sampling should be based on time or frames.
-    let (first_hand_iter, second_hand_iter) = hands_data.get_iters();
-    let elapsed_time = time.elapsed_seconds();
-
-    if let Some(gesture) = analyze_hand_data(first_hand_iter, elapsed_time, &pinch_gesture_info.last_pinch_times) {
-        let hand_type = gesture.hand_type;
-        hand_pinch.send(gesture);
-        pinch_gesture_info.last_pinch_times.entry(hand_type).and_modify(|t| *t = elapsed_time);
-    }
-    if let Some(gesture) = analyze_hand_data(second_hand_iter, elapsed_time, &pinch_gesture_info.last_pinch_times) {
-        let hand_type = gesture.hand_type;
-        hand_pinch.send(gesture);
-        pinch_gesture_info.last_pinch_times.entry(hand_type).and_modify(|t| *t = elapsed_time);
-    }
-}
-
-fn analyze_hand_data<'a>(
-    hand_data: impl Iterator<Item=&'a HandData>,
-    time: f32,
-    last_pinch_map: &HashMap<HandType, f32>,
-) -> Option<PinchGesture> {
-    let mut current_stage = Stage::BeforePinch(0);
-    for hand in hand_data {
-        let pinch_distance = hand.index[0].distance(hand.thumb[0]);
-        let normalized_pinch_distance = normalize_pinch_distance(pinch_distance);
-
-        match current_stage {
-            Stage::BeforePinch(ref mut val) => {
-                if normalized_pinch_distance < PINCH_DISTANCE_THRESHOLD {
-                    *val += 1;
-                } else if *val != 0 {
-                    current_stage = Stage::Pinching(0);
-                } else {
-                    return None;
-                }
+    for (hand_type, finger) in [HandType::Left, HandType::Right]
+        .into_iter()
+        .flat_map(|hand_type| PinchFinger::ALL.map(|finger| (hand_type, finger)))
+    {
+        let state = pinch_gesture_info.fingers.entry((hand_type, finger)).or_default();
+
+        let Some(history) = hands_data.history(hand_type) else {
+            if let (Some(PinchTransition::Released), Some(pinch)) =
+                (state.detector.release(), state.active_pinch.take())
+            {
+                let timestamp = hands_data.latest_timestamp().unwrap_or(pinch.started_at);
+                pinch_released.send(PinchReleased {
+                    hand_type,
+                    finger,
+                    transform: pinch.transform,
+                    duration: micros_between(pinch.started_at, timestamp),
+                });
             }
-            Stage::Pinching(ref mut val) => {
-                if normalized_pinch_distance > PINCH_DISTANCE_THRESHOLD {
-                    *val += 1;
-                } else if *val != 0 {
-                    current_stage = Stage::AfterPinch(0);
-                } else {
-                    return None;
+            state.last_timestamp = None;
+            continue;
+        };
+
+        for sample in history.samples_after(state.last_timestamp.unwrap_or(i64::MIN)) {
+            state.last_timestamp = Some(sample.timestamp);
+            let transform = pinch_transform(&sample.hand, finger);
+            let strength = pinch_strength(&sample.hand, finger, &calibration);
+
+            match state.detector.update(&config, sample.timestamp, strength) {
+                Some(PinchTransition::Started) => {
+                    state.active_pinch = Some(ActivePinch {
+                        started_at: sample.timestamp,
+                        transform,
+                        last_moved: transform.translation,
+                    });
+                    pinch_started.send(PinchStarted {
+                        hand_type,
+                        finger,
+                        transform,
+                    });
                 }
-            }
-            Stage::AfterPinch(ref mut _val) => {
-                if last_pinch_map[&hand.type_] > time - PINCH_GESTURE_MIN_INTERVAL {
-                    return None;
+                Some(PinchTransition::Released) => {
+                    if let Some(pinch) = state.active_pinch.take() {
+                        pinch_released.send(PinchReleased {
+                            hand_type,
+                            finger,
+                            transform: pinch.transform,
+                            duration: micros_between(pinch.started_at, sample.timestamp),
+                        });
+                    }
+                }
+                None => {
+                    let Some(pinch) = state.active_pinch.as_mut() else {
+                        continue;
+                    };
+                    pinch.transform = transform;
+                    let delta = transform.translation - pinch.last_moved;
+                    if delta.length() >= PINCH_MOVE_MIN_DISTANCE * sample.hand.size() {
+                        pinch.last_moved = transform.translation;
+                        pinch_moved.send(PinchMoved {
+                            hand_type,
+                            finger,
+                            transform,
+                            delta,
+                        });
+                    }
                 }
+            }
+        }
 
-                let middle_point = hand.index[0].lerp(hand.thumb[0], 0.5);
-                let pinch_transform = Transform::from_translation(middle_point).looking_at(hand.index[0], Vec3::Y);
+        if let (Some(pinch), Some(timestamp)) = (&state.active_pinch, state.last_timestamp) {
+            pinch_held.send(PinchHeld {
+                hand_type,
+                finger,
+                transform: pinch.transform,
+                duration: micros_between(pinch.started_at, timestamp),
+            });
+        }
+    }
+}
 
-                return Some(PinchGesture {
-                    hand_type: hand.type_,
-                    transform: pinch_transform,
-                });
-            }
+/// Finding 'pinch gesture', i.e. a pinch released shortly after it started.
+pub fn detect_pinch_event(mut pinch_released: EventReader<PinchReleased>, mut hand_pinch: EventWriter<PinchGesture>) {
+    for released in pinch_released.read() {
+        if released.duration <= PINCH_TAP_MAX_DURATION {
+            hand_pinch.send(PinchGesture {
+                hand_type: released.hand_type,
+                finger: released.finger,
+                transform: released.transform,
+            });
         }
     }
+}
+
+/// Strength of the pinch between the thumb and `finger`, from 0 to 1, calculated from the distance of their tips
+/// with [`PinchCalibration::normalize`]; see [`PinchCalibration::use_tracker_strength`] for the tracker value.
+/// The strength is lowered while another finger tip is about as close to the thumb, so touching two tips does not
+/// pinch both fingers; it changes smoothly with the distances, so noise does not move the pinch between fingers.
+pub fn pinch_strength(hand: &HandData, finger: PinchFinger, calibration: &PinchCalibration) -> f32 {
+    let distance = |finger: PinchFinger| relative_pinch_distance(hand, finger.joints(hand)[0]);
+    let Some(finger_distance) = distance(finger) else {
+        // distances cannot be measured without the hand size; the tracker value is not specific to a finger
+        return match finger {
+            PinchFinger::Index => hand.pinch_strength.unwrap_or(0.0),
+            _ => 0.0,
+        };
+    };
+
+    let closest_other_distance = PinchFinger::ALL
+        .into_iter()
+        .filter(|other| *other != finger)
+        .filter_map(distance)
+        .fold(f32::INFINITY, f32::min);
+    // 1 while the other tips are at least twice as far from the thumb, 0 when one of them is closer
+    let selectivity = (2.0 * (1.0 - finger_distance / closest_other_distance.max(f32::EPSILON))).clamp(0.0, 1.0);
+
+    let strength = match hand.pinch_strength {
+        Some(strength) if calibration.use_tracker_strength => strength,
+        _ => calibration.normalize(finger_distance),
+    };
+    strength * selectivity
+}
+
+fn pinch_transform(hand: &HandData, finger: PinchFinger) -> Transform {
+    let tip = finger.joints(hand)[0];
+    let middle_point = tip.lerp(hand.thumb[0], 0.5);
+    Transform::from_translation(middle_point).looking_at(tip, Vec3::Y)
+}
 
-    None
+fn micros_between(from: i64, to: i64) -> Duration {
+    Duration::from_micros((to - from).max(0) as u64)
 }
 
-fn normalize_pinch_distance(distance: f32) -> f32 {
-    ((MIN_PINCH_DISTANCE - distance) / MAX_PINCH_DISTANCE).clamp(0.0, 1.0)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Time between frames, in microseconds.
+    const FRAME_INTERVAL: i64 = 10_000;
+    /// Deterministic noise added to the strength, larger than half of the gap between the thresholds.
+    const NOISE: [f32; 6] = [0.12, -0.12, 0.05, -0.1, 0.12, -0.08];
+
+    /// Runs a detector over strengths of consecutive frames and returns its transitions.
+    fn transitions(strengths: impl IntoIterator<Item = f32>) -> Vec<PinchTransition> {
+        let config = PinchConfig::default();
+        let mut detector = PinchDetector::default();
+        strengths
+            .into_iter()
+            .enumerate()
+            .filter_map(|(frame, strength)| detector.update(&config, frame as i64 * FRAME_INTERVAL, strength))
+            .collect()
+    }
+
+    /// Strength rising from 0 to 1, held, and falling back to 0, with noise added to every frame.
+    fn noisy_pinch() -> Vec<f32> {
+        let ramp = (0..=20).map(|step| step as f32 / 20.0);
+        ramp.clone()
+            .chain([1.0; 20])
+            .chain(ramp.rev())
+            .chain([0.0; 10])
+            .zip(NOISE.iter().cycle())
+            .map(|(strength, noise)| strength + noise)
+            .collect()
+    }
+
+    #[test]
+    fn noisy_pinch_is_started_and_released_once() {
+        assert_eq!(
+            transitions(noisy_pinch()),
+            [PinchTransition::Started, PinchTransition::Released]
+        );
+    }
+
+    #[test]
+    fn repeated_noisy_pinches_are_reported_separately() {
+        let strengths = noisy_pinch().into_iter().chain(noisy_pinch());
+
+        assert_eq!(
+            transitions(strengths),
+            [
+                PinchTransition::Started,
+                PinchTransition::Released,
+                PinchTransition::Started,
+                PinchTransition::Released
+            ]
+        );
+    }
+
+    #[test]
+    fn single_frame_above_engage_threshold_does_not_start_pinch() {
+        // the strength stays between the thresholds after the spike, for much longer than min_hold
+        let strengths = [0.4, 0.9].into_iter().chain([0.6; 20]).chain([0.4; 5]);
+
+        assert_eq!(transitions(strengths), []);
+    }
+
+    #[test]
+    fn pinch_shorter_than_min_hold_is_not_reported() {
+        let strengths = [0.0, 0.9, 0.9, 0.0, 0.0];
+
+        assert_eq!(transitions(strengths), []);
+    }
+
+    #[test]
+    fn released_detector_reports_release_only_while_pinched() {
+        let config = PinchConfig::default();
+        let mut detector = PinchDetector::default();
+        assert_eq!(detector.release(), None);
+
+        for frame in 0..5 {
+            detector.update(&config, frame * FRAME_INTERVAL, 1.0);
+        }
+        assert!(detector.is_pinching());
+        assert_eq!(detector.release(), Some(PinchTransition::Released));
+        assert!(!detector.is_pinching());
+    }
 }
@@ -0,0 +1,128 @@
+use bevy::math::primitives::Direction3d;
+use bevy::math::Ray3d;
+use bevy::prelude::{Event, Resource};
+
+use crate::gesture::Gesture;
+use crate::models::{finger_curl, finger_extension, HandData, HandType};
+use crate::{HandHistory, HandSample};
+
+/// Finger extension and curl limits of [`PointDetector`]. Pointing starts above the first pair of thresholds
+/// and ends below the lower release pair, so a finger held near a limit does not flicker.
+#[derive(Resource, Debug, Clone)]
+pub struct PointConfig {
+    /// Extension of the index finger above which it is considered pointing.
+    pub index_extended_threshold: f32,
+    /// Extension of the index finger below which pointing ends.
+    pub index_release_threshold: f32,
+    /// Curl of middle, ring and pinky fingers above which they are considered curled.
+    pub fingers_curled_threshold: f32,
+    /// Curl of middle, ring and pinky fingers below which pointing ends.
+    pub fingers_release_threshold: f32,
+}
+
+impl Default for PointConfig {
+    fn default() -> Self {
+        Self {
+            index_extended_threshold: 0.9,
+            index_release_threshold: 0.8,
+            fingers_curled_threshold: 0.5,
+            fingers_release_threshold: 0.35,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PointPhase {
+    Started,
+    /// Sent once per app update while the hand is pointing.
+    Held,
+    /// Sent when the hand stops pointing or is lost by the tracker.
+    Ended,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct PointEvent {
+    pub hand_type: HandType,
+    pub phase: PointPhase,
+    /// starts at the index finger tip and goes along the finger; the last known ray when pointing ended
+    pub ray: Ray3d,
+}
+
+#[derive(Default)]
+pub struct PointState {
+    /// Ray of the newest frame, while the hand is pointing.
+    ray: Option<Ray3d>,
+}
+
+impl PointState {
+    pub fn is_pointing(&self) -> bool {
+        self.ray.is_some()
+    }
+}
+
+/// Detector of [`PointEvent`], in which the index finger is extended and middle, ring and pinky fingers are curled.
+/// The thumb is ignored, as it is tracked poorly when hidden behind other fingers.
+pub struct PointDetector;
+
+impl Gesture for PointDetector {
+    const NAME: &'static str = "point";
+    type Event = PointEvent;
+    type Config = PointConfig;
+    type HandState = PointState;
+
+    fn update(
+        config: &PointConfig,
+        state: &mut PointState,
+        hand_type: HandType,
+        _history: &HandHistory,
+        new_samples: &[&HandSample],
+    ) -> Option<PointEvent> {
+        let hand = &new_samples.last()?.hand;
+        let ray = Some(hand)
+            .filter(|hand| is_pointing(hand, state.is_pointing(), config))
+            .and_then(pointing_ray);
+
+        let (phase, ray) = match (ray, state.ray) {
+            (Some(ray), None) => (PointPhase::Started, ray),
+            (Some(ray), Some(_)) => (PointPhase::Held, ray),
+            (None, Some(last_ray)) => (PointPhase::Ended, last_ray),
+            (None, None) => return None,
+        };
+        state.ray = (phase != PointPhase::Ended).then_some(ray);
+
+        Some(PointEvent { hand_type, phase, ray })
+    }
+
+    fn hand_lost(_config: &PointConfig, state: PointState, hand_type: HandType) -> Option<PointEvent> {
+        Some(PointEvent {
+            hand_type,
+            phase: PointPhase::Ended,
+            ray: state.ray?,
+        })
+    }
+}
+
+/// Uses lower thresholds for a hand which is already pointing, so pointing does not flicker.
+fn is_pointing(hand: &HandData, was_pointing: bool, config: &PointConfig) -> bool {
+    let (index_threshold, fingers_threshold) = if was_pointing {
+        (config.index_release_threshold, config.fingers_release_threshold)
+    } else {
+        (config.index_extended_threshold, config.fingers_curled_threshold)
+    };
+
+    let palm_center = hand.palm_center();
+    finger_extension(&hand.index) > index_threshold
+        && [&hand.middle, &hand.ring, &hand.pinky]
+            .iter()
+            .all(|finger| finger_curl(finger, palm_center) > fingers_threshold)
+}
+
+/// Ray from the index finger tip, in the direction from the knuckle to the tip.
+fn pointing_ray(hand: &HandData) -> Option<Ray3d> {
+    let direction = Direction3d::new(hand.index[0] - hand.index[3]).ok()?;
+
+    Some(Ray3d {
+        origin: hand.index[0],
+        direction,
+    })
+}
@@ -0,0 +1,464 @@
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use bevy::app::{App, Plugin, Update};
+use bevy::log::{error, warn};
+use bevy::math::Vec3;
+use bevy::prelude::{IntoSystemConfigs, Res, ResMut, Resource};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Arm, Finger, HandData, HandType, Palm};
+use crate::source::{TrackingFrame, UpdateHandsData};
+use crate::HandsData;
+
+/// Version of the recording format written by [`RecordingWriter`].
+///
+/// Recordings store their own description of a hand instead of [`HandData`], so that changes
+/// of [`HandData`] do not break old files. New fields have to be optional; other changes
+/// require bumping this version and converting older records in [`RecordedHand::into_hand_data`].
+pub const RECORDING_FORMAT_VERSION: u32 = 1;
+const RECORDING_FORMAT_NAME: &str = "hands_tracking_recording";
+
+pub struct RecordingPlugin;
+
+impl Plugin for RecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HandsRecorder::default())
+            .add_systems(Update, record_hands_data.after(UpdateHandsData));
+    }
+}
+
+#[derive(Debug)]
+pub enum RecordingError {
+    Io(std::io::Error),
+    Parse { line: usize, error: serde_json::Error },
+    UnknownFormat,
+    UnsupportedVersion(u32),
+}
+
+impl Display for RecordingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordingError::Io(error) => write!(f, "recording io error: {error}"),
+            RecordingError::Parse { line, error } => write!(f, "invalid recording, line {line}: {error}"),
+            RecordingError::UnknownFormat => write!(f, "file is not a hands tracking recording"),
+            RecordingError::UnsupportedVersion(version) => write!(
+                f,
+                "recording version {version} is newer than supported version {RECORDING_FORMAT_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RecordingError {}
+
+impl From<std::io::Error> for RecordingError {
+    fn from(error: std::io::Error) -> Self {
+        RecordingError::Io(error)
+    }
+}
+
+/// First line of a recording file. Every following line is a single [`RecordedFrame`].
+#[derive(Serialize, Deserialize)]
+struct RecordingHeader {
+    format: String,
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedFrame {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    frame_id: Option<i64>,
+    timestamp: i64,
+    hands: [Option<RecordedHand>; 2],
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone)]
+enum RecordedHandType {
+    Left,
+    Right,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedHand {
+    hand_type: RecordedHandType,
+    confidence: f32,
+    thumb: [[f32; 3]; 5],
+    index: [[f32; 3]; 5],
+    middle: [[f32; 3]; 5],
+    ring: [[f32; 3]; 5],
+    pinky: [[f32; 3]; 5],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    palm: Option<RecordedPalm>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    arm: Option<RecordedArm>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    grab_strength: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pinch_strength: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedPalm {
+    position: [f32; 3],
+    normal: [f32; 3],
+    direction: [f32; 3],
+    velocity: [f32; 3],
+    width: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedArm {
+    wrist: [f32; 3],
+    elbow: [f32; 3],
+    width: f32,
+}
+
+impl RecordedHand {
+    fn from_hand_data(hand: &HandData) -> Self {
+        let finger = |finger: &Finger| finger.map(|joint| joint.to_array());
+
+        Self {
+            hand_type: match hand.type_ {
+                HandType::Left => RecordedHandType::Left,
+                HandType::Right => RecordedHandType::Right,
+            },
+            confidence: hand.confidence,
+            thumb: finger(&hand.thumb),
+            index: finger(&hand.index),
+            middle: finger(&hand.middle),
+            ring: finger(&hand.ring),
+            pinky: finger(&hand.pinky),
+            id: hand.id,
+            palm: hand.palm.map(|palm| RecordedPalm {
+                position: palm.position.to_array(),
+                normal: palm.normal.to_array(),
+                direction: palm.direction.to_array(),
+                velocity: palm.velocity.to_array(),
+                width: palm.width,
+            }),
+            arm: hand.arm.map(|arm| RecordedArm {
+                wrist: arm.wrist.to_array(),
+                elbow: arm.elbow.to_array(),
+                width: arm.width,
+            }),
+            grab_strength: hand.grab_strength,
+            pinch_strength: hand.pinch_strength,
+        }
+    }
+
+    fn into_hand_data(self) -> HandData {
+        let finger = |finger: [[f32; 3]; 5]| finger.map(Vec3::from_array);
+
+        HandData {
+            id: self.id,
+            type_: match self.hand_type {
+                RecordedHandType::Left => HandType::Left,
+                RecordedHandType::Right => HandType::Right,
+            },
+            confidence: self.confidence,
+            thumb: finger(self.thumb),
+            index: finger(self.index),
+            middle: finger(self.middle),
+            ring: finger(self.ring),
+            pinky: finger(self.pinky),
+            palm: self.palm.map(|palm| Palm {
+                position: Vec3::from_array(palm.position),
+                normal: Vec3::from_array(palm.normal),
+                direction: Vec3::from_array(palm.direction),
+                velocity: Vec3::from_array(palm.velocity),
+                width: palm.width,
+            }),
+            arm: self.arm.map(|arm| Arm {
+                wrist: Vec3::from_array(arm.wrist),
+                elbow: Vec3::from_array(arm.elbow),
+                width: arm.width,
+            }),
+            grab_strength: self.grab_strength,
+            pinch_strength: self.pinch_strength,
+        }
+    }
+}
+
+/// Writes tracking frames to a file, one JSON object per line.
+pub struct RecordingWriter {
+    writer: BufWriter<File>,
+}
+
+impl RecordingWriter {
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let header = RecordingHeader {
+            format: RECORDING_FORMAT_NAME.to_string(),
+            version: RECORDING_FORMAT_VERSION,
+        };
+        write_line(&mut writer, &header)?;
+
+        Ok(Self { writer })
+    }
+
+    pub fn write_frame(&mut self, frame: &TrackingFrame) -> Result<(), RecordingError> {
+        let recorded_frame = RecordedFrame {
+            frame_id: Some(frame.frame_id),
+            timestamp: frame.timestamp,
+            hands: frame
+                .hands
+                .each_ref()
+                .map(|hand| hand.as_ref().map(RecordedHand::from_hand_data)),
+        };
+
+        write_line(&mut self.writer, &recorded_frame)
+    }
+
+    pub fn finish(mut self) -> Result<(), RecordingError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+fn write_line<T: Serialize>(writer: &mut impl Write, value: &T) -> Result<(), RecordingError> {
+    serde_json::to_writer(&mut *writer, value).map_err(std::io::Error::from)?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Reads all frames of a recording, oldest first.
+pub fn read_recording(path: impl AsRef<Path>) -> Result<Vec<TrackingFrame>, RecordingError> {
+    let mut lines = BufReader::new(File::open(path)?).lines();
+
+    let header_line = lines.next().ok_or(RecordingError::UnknownFormat)??;
+    let header: RecordingHeader = serde_json::from_str(&header_line).map_err(|_| RecordingError::UnknownFormat)?;
+    if header.format != RECORDING_FORMAT_NAME {
+        return Err(RecordingError::UnknownFormat);
+    }
+    if header.version > RECORDING_FORMAT_VERSION {
+        return Err(RecordingError::UnsupportedVersion(header.version));
+    }
+
+    let mut frames = Vec::new();
+    for (line_index, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let recorded_frame: RecordedFrame = serde_json::from_str(&line).map_err(|error| RecordingError::Parse {
+            line: line_index + 2,
+            error,
+        })?;
+        frames.push(TrackingFrame {
+            // recordings made before frame ids were stored use frame indices
+            frame_id: recorded_frame.frame_id.unwrap_or(frames.len() as i64),
+            timestamp: recorded_frame.timestamp,
+            hands: recorded_frame.hands.map(|hand| hand.map(RecordedHand::into_hand_data)),
+        });
+    }
+
+    Ok(frames)
+}
+
+/// Records every frame pushed to [`HandsData`] while started.
+#[derive(Resource, Default)]
+pub struct HandsRecorder {
+    writer: Option<RecordingWriter>,
+    path: Option<PathBuf>,
+    /// Value of [`HandsData::pushed_frames`] for the last recorded frame.
+    last_recorded_frame: Option<u64>,
+}
+
+impl HandsRecorder {
+    pub fn start(&mut self, path: impl Into<PathBuf>) -> Result<(), RecordingError> {
+        self.stop()?;
+
+        let path = path.into();
+        self.writer = Some(RecordingWriter::create(&path)?);
+        self.path = Some(path);
+        self.last_recorded_frame = None;
+
+        Ok(())
+    }
+
+    pub fn stop(&mut self) -> Result<(), RecordingError> {
+        self.path = None;
+        match self.writer.take() {
+            Some(writer) => writer.finish(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Path of the file being currently recorded.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+}
+
+fn record_hands_data(hands_data: Res<HandsData>, mut recorder: ResMut<HandsRecorder>) {
+    let recorder = &mut *recorder;
+    let pushed_frames = hands_data.pushed_frames();
+    let Some(writer) = recorder.writer.as_mut() else {
+        return;
+    };
+
+    // recording starts with frames pushed after it was started
+    let last_recorded_frame = recorder.last_recorded_frame.unwrap_or(pushed_frames);
+    let new_frames = (pushed_frames - last_recorded_frame) as usize;
+    let kept_frames = hands_data.frames().count();
+    if new_frames > kept_frames {
+        warn!("recorder is lagging, {} frames were lost", new_frames - kept_frames);
+    }
+
+    let skip = kept_frames.saturating_sub(new_frames);
+    for frame in hands_data.frames().skip(skip) {
+        if let Err(error) = writer.write_frame(frame) {
+            error!("{error}, recording stopped");
+            recorder.writer = None;
+            recorder.path = None;
+            return;
+        }
+    }
+
+    recorder.last_recorded_frame = Some(pushed_frames);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn hand(type_: HandType) -> HandData {
+        let finger = |offset: f32| [0.0, 1.0, 2.0, 3.0, 4.0].map(|joint| Vec3::new(offset, joint, -joint));
+        HandData {
+            id: Some(7),
+            type_,
+            confidence: 0.9,
+            thumb: finger(0.0),
+            index: finger(1.0),
+            middle: finger(2.0),
+            ring: finger(3.0),
+            pinky: finger(4.0),
+            palm: Some(Palm {
+                position: Vec3::new(1.0, 2.0, 3.0),
+                normal: Vec3::NEG_Y,
+                direction: Vec3::NEG_Z,
+                velocity: Vec3::X,
+                width: 80.0,
+            }),
+            arm: Some(Arm {
+                wrist: Vec3::new(0.0, 0.0, 50.0),
+                elbow: Vec3::new(0.0, -100.0, 250.0),
+                width: 60.0,
+            }),
+            grab_strength: Some(0.25),
+            pinch_strength: None,
+        }
+    }
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    #[test]
+    fn written_frames_are_read_back() {
+        let path = std::env::temp_dir().join(format!("hand_gestures_recording_{}.jsonl", std::process::id()));
+        let frames = [
+            TrackingFrame {
+                frame_id: 10,
+                timestamp: 1_000,
+                hands: [Some(hand(HandType::Left)), None],
+            },
+            TrackingFrame {
+                frame_id: 11,
+                timestamp: 12_000,
+                hands: [Some(hand(HandType::Left)), Some(hand(HandType::Right))],
+            },
+        ];
+
+        let mut writer = RecordingWriter::create(&path).unwrap();
+        for frame in &frames {
+            writer.write_frame(frame).unwrap();
+        }
+        writer.finish().unwrap();
+        let read_frames = read_recording(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let read_frames = read_frames.unwrap();
+        assert_eq!(read_frames.len(), frames.len());
+        for (read, written) in read_frames.iter().zip(&frames) {
+            assert_eq!(read.frame_id, written.frame_id);
+            assert_eq!(read.timestamp, written.timestamp);
+            for (read_hand, written_hand) in read.hands.iter().zip(&written.hands) {
+                let (Some(read_hand), Some(written_hand)) = (read_hand, written_hand) else {
+                    assert_eq!(read_hand.is_some(), written_hand.is_some());
+                    continue;
+                };
+                assert_eq!(read_hand.type_, written_hand.type_);
+                assert_eq!(read_hand.id, written_hand.id);
+                assert_eq!(read_hand.confidence, written_hand.confidence);
+                assert_eq!(
+                    [
+                        read_hand.thumb,
+                        read_hand.index,
+                        read_hand.middle,
+                        read_hand.ring,
+                        read_hand.pinky
+                    ],
+                    [
+                        written_hand.thumb,
+                        written_hand.index,
+                        written_hand.middle,
+                        written_hand.ring,
+                        written_hand.pinky
+                    ]
+                );
+                assert_eq!(
+                    read_hand.palm.map(|palm| palm.velocity),
+                    written_hand.palm.map(|palm| palm.velocity)
+                );
+                assert_eq!(
+                    read_hand.arm.map(|arm| arm.elbow),
+                    written_hand.arm.map(|arm| arm.elbow)
+                );
+                assert_eq!(read_hand.grab_strength, written_hand.grab_strength);
+                assert_eq!(read_hand.pinch_strength, written_hand.pinch_strength);
+            }
+        }
+    }
+
+    #[test]
+    fn version_1_recording_without_optional_fields_is_read() {
+        let frames = read_recording(fixture("recording_v1_minimal.jsonl")).unwrap();
+
+        assert_eq!(frames.len(), 2);
+        // frame ids are missing, so frame indices are used
+        assert_eq!(frames.iter().map(|frame| frame.frame_id).collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(frames[1].timestamp, 11_111);
+        assert!(frames[0].hands[1].is_none());
+
+        let hand = frames[0].hands[0].as_ref().unwrap();
+        assert_eq!(hand.type_, HandType::Right);
+        assert_eq!(hand.index[0], Vec3::new(10.0, 200.0, -30.0));
+        assert!(hand.id.is_none() && hand.palm.is_none() && hand.arm.is_none());
+        assert!(hand.grab_strength.is_none() && hand.pinch_strength.is_none());
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let path = std::env::temp_dir().join(format!("hand_gestures_recording_v2_{}.jsonl", std::process::id()));
+        std::fs::write(&path, "{\"format\":\"hands_tracking_recording\",\"version\":2}\n").unwrap();
+        let result = read_recording(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(RecordingError::UnsupportedVersion(2))));
+    }
+}
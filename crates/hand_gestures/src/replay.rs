@@ -0,0 +1,339 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::IntoSystemConfigs;
+
+use crate::recording::{read_recording, RecordingError};
+use crate::source::{update_hands_data, HandTrackingSource, TrackingFrame, UpdateHandsData};
+
+/// Feeds a recorded session into [`crate::HandsData`], in place of a live input plugin.
+pub struct ReplaySourcePlugin {
+    pub source: ReplaySource,
+}
+
+impl ReplaySourcePlugin {
+    /// Loads a looping replay of the recording at `path`.
+    pub fn from_file(path: impl Into<PathBuf>) -> Result<Self, RecordingError> {
+        let mut source = ReplaySource::from_file(path)?;
+        source.set_looping(true);
+        Ok(Self { source })
+    }
+}
+
+impl Plugin for ReplaySourcePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_non_send_resource(self.source.clone())
+            .add_systems(Update, update_hands_data::<ReplaySource>.in_set(UpdateHandsData));
+    }
+}
+
+/// [`HandTrackingSource`] playing back recorded frames.
+///
+/// Timestamps of produced frames always increase, also after seeking or looping, so gesture detectors,
+/// which measure time with them, follow the playback time. Hand histories are cleared after such jumps.
+#[derive(Clone)]
+pub struct ReplaySource {
+    frames: Vec<TrackingFrame>,
+    /// Playback position in microseconds, relative to the first frame.
+    position: i64,
+    /// Index of the next frame to play.
+    cursor: usize,
+    /// Added to recorded timestamps to keep produced timestamps continuous.
+    timestamp_offset: i64,
+    speed: f32,
+    looping: bool,
+    paused: bool,
+    /// Set when the playback jumped, until reported by [`HandTrackingSource::take_discontinuity`].
+    discontinuity: bool,
+}
+
+impl ReplaySource {
+    pub fn new(frames: Vec<TrackingFrame>) -> Self {
+        Self {
+            frames,
+            position: 0,
+            cursor: 0,
+            timestamp_offset: 0,
+            speed: 1.0,
+            looping: false,
+            paused: false,
+            discontinuity: false,
+        }
+    }
+
+    pub fn from_file(path: impl Into<PathBuf>) -> Result<Self, RecordingError> {
+        read_recording(path.into()).map(Self::new)
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn play(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Playback speed multiplier; negative values are treated as 0.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0);
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    pub fn position(&self) -> Duration {
+        Duration::from_micros(self.position as u64)
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_micros(self.duration_micros() as u64)
+    }
+
+    /// Returns true if the end of a not looping replay was reached.
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.frames.len()
+    }
+
+    /// Moves the playback to `position`, clamped to the recording duration.
+    /// The frame at `position` is produced by the next poll.
+    pub fn seek(&mut self, position: Duration) {
+        let position = (position.as_micros() as i64).min(self.duration_micros());
+
+        self.timestamp_offset += self.position - position + self.jump_gap_micros();
+        self.position = position;
+        self.cursor = self
+            .frames
+            .partition_point(|frame| self.relative_time(frame) < position);
+        self.discontinuity = true;
+    }
+
+    fn relative_time(&self, frame: &TrackingFrame) -> i64 {
+        frame.timestamp - self.frames[0].timestamp
+    }
+
+    fn duration_micros(&self) -> i64 {
+        self.frames.last().map_or(0, |frame| self.relative_time(frame))
+    }
+
+    /// Gap added to timestamps when the playback jumps by seeking or looping, so they do not repeat;
+    /// equal to the interval between the first two frames.
+    fn jump_gap_micros(&self) -> i64 {
+        match self.frames.get(1) {
+            Some(frame) => self.relative_time(frame).max(1),
+            None => 1,
+        }
+    }
+}
+
+impl HandTrackingSource for ReplaySource {
+    fn poll_frames(&mut self, delta: Duration) -> Vec<TrackingFrame> {
+        if self.paused || self.frames.is_empty() {
+            return Vec::new();
+        }
+
+        let mut frames = Vec::new();
+        let target = self.position + (delta.as_micros() as f64 * self.speed as f64) as i64;
+        while let Some(frame) = self.frames.get(self.cursor) {
+            if self.relative_time(frame) > target {
+                break;
+            }
+
+            let mut frame = frame.clone();
+            frame.timestamp += self.timestamp_offset;
+            frames.push(frame);
+            self.cursor += 1;
+        }
+
+        let duration = self.duration_micros();
+        if target <= duration || !self.looping {
+            self.position = target.min(duration);
+            return frames;
+        }
+
+        // frames of the next loop are produced by the following polls, after the hand histories are cleared;
+        // whole loops are skipped if delta is longer than the recording
+        let loop_length = duration + self.jump_gap_micros();
+        let loops = (target / loop_length).max(1);
+        // target is negative while in the gap between loops
+        self.position = (target - loops * loop_length).max(0);
+        self.timestamp_offset += loops * loop_length;
+        self.cursor = 0;
+        self.discontinuity = true;
+
+        frames
+    }
+
+    fn take_discontinuity(&mut self) -> bool {
+        std::mem::take(&mut self.discontinuity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::MinimalPlugins;
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::{GesturePlugin, HandsData};
+
+    /// Time between recorded frames, in microseconds.
+    const FRAME_INTERVAL: i64 = 10_000;
+
+    /// Replay of 11 frames, 100 ms long, recorded with timestamps starting at 5 s.
+    fn replay() -> ReplaySource {
+        ReplaySource::new(
+            (0..=10)
+                .map(|index| TrackingFrame {
+                    frame_id: index,
+                    timestamp: 5_000_000 + index * FRAME_INTERVAL,
+                    hands: [None, None],
+                })
+                .collect(),
+        )
+    }
+
+    fn frame_ids(frames: &[TrackingFrame]) -> Vec<i64> {
+        frames.iter().map(|frame| frame.frame_id).collect()
+    }
+
+    #[test]
+    fn frames_are_produced_at_recorded_pace() {
+        let mut replay = replay();
+
+        assert_eq!(frame_ids(&replay.poll_frames(Duration::ZERO)), [0]);
+        assert_eq!(frame_ids(&replay.poll_frames(Duration::from_millis(25))), [1, 2]);
+        assert_eq!(replay.position(), Duration::from_millis(25));
+    }
+
+    #[test]
+    fn seek_moves_forward_and_back_with_increasing_timestamps() {
+        let mut replay = replay();
+        let mut timestamps = Vec::new();
+
+        timestamps.extend(replay.poll_frames(Duration::from_millis(20)));
+        replay.seek(Duration::from_millis(70));
+        assert!(replay.take_discontinuity());
+        assert!(!replay.take_discontinuity());
+        let after_forward_seek = replay.poll_frames(Duration::from_millis(10));
+        assert_eq!(frame_ids(&after_forward_seek), [7, 8]);
+        timestamps.extend(after_forward_seek);
+
+        replay.seek(Duration::from_millis(30));
+        assert!(replay.take_discontinuity());
+        let after_back_seek = replay.poll_frames(Duration::ZERO);
+        assert_eq!(frame_ids(&after_back_seek), [3]);
+        timestamps.extend(after_back_seek);
+
+        let timestamps = timestamps.iter().map(|frame| frame.timestamp).collect::<Vec<_>>();
+        assert!(timestamps.windows(2).all(|pair| pair[0] < pair[1]), "{timestamps:?}");
+    }
+
+    #[test]
+    fn seek_is_clamped_to_duration() {
+        let mut replay = replay();
+
+        replay.seek(Duration::from_secs(10));
+
+        assert_eq!(replay.position(), replay.duration());
+        assert_eq!(frame_ids(&replay.poll_frames(Duration::ZERO)), [10]);
+        assert!(replay.is_finished());
+    }
+
+    #[test]
+    fn looping_replay_wraps_with_increasing_timestamps() {
+        let mut replay = replay();
+        replay.set_looping(true);
+        let mut frames = Vec::new();
+        let mut discontinuities = 0;
+
+        // 2.5 loops in steps of 15 ms
+        for _ in 0..19 {
+            frames.extend(replay.poll_frames(Duration::from_millis(15)));
+            discontinuities += replay.take_discontinuity() as usize;
+        }
+
+        assert_eq!(discontinuities, 2);
+        assert_eq!(frame_ids(&frames[..13]), [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 0, 1]);
+        let timestamps = frames.iter().map(|frame| frame.timestamp).collect::<Vec<_>>();
+        assert!(timestamps.windows(2).all(|pair| pair[0] < pair[1]), "{timestamps:?}");
+        // the gap between loops is one frame interval, like between any other frames
+        assert_eq!(timestamps[11] - timestamps[10], FRAME_INTERVAL);
+    }
+
+    #[test]
+    fn not_looping_replay_stops_at_the_end() {
+        let mut replay = replay();
+
+        assert_eq!(replay.poll_frames(Duration::from_secs(1)).len(), 11);
+        assert!(replay.poll_frames(Duration::from_secs(1)).is_empty());
+        assert!(replay.is_finished());
+        assert!(!replay.take_discontinuity());
+    }
+
+    #[test]
+    fn double_speed_advances_timestamps_twice_as_fast() {
+        let mut replay = replay();
+        replay.set_speed(2.0);
+
+        let frames = replay.poll_frames(Duration::from_millis(40));
+
+        assert_eq!(frame_ids(&frames), [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(
+            frames.last().unwrap().timestamp - frames[0].timestamp,
+            2 * Duration::from_millis(40).as_micros() as i64
+        );
+        assert_eq!(replay.position(), Duration::from_millis(80));
+    }
+
+    #[test]
+    fn paused_replay_produces_no_frames() {
+        let mut replay = replay();
+        replay.poll_frames(Duration::from_millis(20));
+
+        replay.pause();
+        assert!(replay.poll_frames(Duration::from_millis(50)).is_empty());
+        assert_eq!(replay.position(), Duration::from_millis(20));
+
+        replay.play();
+        assert_eq!(frame_ids(&replay.poll_frames(Duration::from_millis(10))), [3]);
+    }
+
+    #[test]
+    fn update_after_seek_is_not_lost() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            GesturePlugin::empty(),
+            ReplaySourcePlugin { source: replay() },
+        ))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(10)));
+        // the first update of the app has no delta
+        app.update();
+
+        app.world
+            .non_send_resource_mut::<ReplaySource>()
+            .seek(Duration::from_millis(40));
+        app.update();
+        assert!(app.world.resource::<HandsData>().latest_timestamp().is_none());
+        app.update();
+
+        assert_eq!(
+            app.world.non_send_resource::<ReplaySource>().position(),
+            Duration::from_millis(60)
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use bevy::prelude::{Local, NonSendMut, Real, Res, ResMut, SystemSet, Time};
+
+use crate::{HandsData, TwoHandsData};
+
+/// Set containing systems which populate [`HandsData`] from a [`HandTrackingSource`].
+/// Gesture detection runs after it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UpdateHandsData;
+
+/// Single frame of tracking data produced by a [`HandTrackingSource`].
+#[derive(Clone)]
+pub struct TrackingFrame {
+    /// Identifier of the frame assigned by the source; increases with every frame of a live source.
+    pub frame_id: i64,
+    /// Time at which the frame was captured by the tracker, in microseconds.
+    /// Gesture detectors measure time with these timestamps instead of the app clock,
+    /// so they behave the same for live and replayed frames.
+    pub timestamp: i64,
+    pub hands: TwoHandsData,
+}
+
+/// External source of hands data, e.g. LeapC.
+///
+/// Sources are stored as non-send resources, so implementations can hold handles
+/// which must stay on the main thread.
+pub trait HandTrackingSource: 'static {
+    /// Returns frames produced since the previous call, oldest first.
+    /// `delta` is the real time elapsed since the previous call.
+    fn poll_frames(&mut self, delta: Duration) -> Vec<TrackingFrame>;
+
+    /// Returns true once after the source jumped in time, e.g. a replay was seeked.
+    /// [`HandsData`] is then cleared before the next frames are polled.
+    fn take_discontinuity(&mut self) -> bool {
+        false
+    }
+}
+
+/// Moves frames from the source `S` into [`HandsData`].
+/// Input plugins register it in the [`UpdateHandsData`] set.
+pub fn update_hands_data<S: HandTrackingSource>(
+    mut source: NonSendMut<S>,
+    mut hands_data: ResMut<HandsData>,
+    time: Res<Time<Real>>,
+    mut skipped_delta: Local<Duration>,
+) {
+    // frames after the jump are polled in the next update, so gesture detectors see the hands lost in between;
+    // the time of this update is passed to that poll, so the source does not fall behind
+    if source.take_discontinuity() {
+        hands_data.clear();
+        *skipped_delta += time.delta();
+        return;
+    }
+
+    let delta = std::mem::take(&mut *skipped_delta) + time.delta();
+    for frame in source.poll_frames(delta) {
+        hands_data.push_overwrite(frame);
+    }
+}
@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use bevy::prelude::{Event, Resource};
+
+use crate::gesture::Gesture;
+use crate::models::{finger_curl, finger_extension, Finger, HandData, HandType};
+use crate::pinch_calibration::relative_pinch_distance;
+use crate::{as_micros, HandHistory, HandSample};
+
+/// Named static pose of a hand.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StaticPose {
+    /// None of the known poses.
+    #[default]
+    Unknown,
+    OpenPalm,
+    Fist,
+    Point,
+    ThumbsUp,
+    Peace,
+    Ok,
+    FingerGun,
+    Rock,
+}
+
+/// Limits used by [`classify_pose`] to decide which fingers are extended or curled and where the thumb is.
+/// A finger between the extended and curled thresholds is neither, so poses which depend on it are not matched.
+#[derive(Resource, Debug, Clone)]
+pub struct PoseConfig {
+    /// Extension (see [`finger_extension`]) above which a finger is considered extended.
+    pub extended_threshold: f32,
+    /// Curl (see [`finger_curl`]) above which a finger is considered curled.
+    pub curled_threshold: f32,
+    /// Distance between the thumb tip and the index knuckle, relative to the hand size,
+    /// above which the thumb is considered sticking out of the hand.
+    pub thumb_out_distance: f32,
+    /// Distance between the thumb and index tips, relative to the hand size,
+    /// below which they are considered touching in the OK pose.
+    pub ok_touch_distance: f32,
+    /// Time a new pose has to be held before [`PoseChanged`] is sent; filters out transitional poses.
+    pub min_pose_duration: Duration,
+}
+
+impl Default for PoseConfig {
+    fn default() -> Self {
+        Self {
+            extended_threshold: 0.9,
+            curled_threshold: 0.5,
+            thumb_out_distance: 1.2,
+            ok_touch_distance: 0.6,
+            min_pose_duration: Duration::from_millis(100),
+        }
+    }
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct PoseChanged {
+    pub hand: HandType,
+    pub from: StaticPose,
+    pub to: StaticPose,
+}
+
+#[derive(Default)]
+pub struct PoseState {
+    pose: StaticPose,
+    /// Pose different from the current one, with the timestamp of the frame it was first seen in.
+    candidate: Option<(StaticPose, i64)>,
+}
+
+impl PoseState {
+    /// Returns the current pose of the hand.
+    pub fn pose(&self) -> StaticPose {
+        self.pose
+    }
+}
+
+/// Classifies a single frame of the hand from extension and curl of its fingers.
+pub fn classify_pose(hand: &HandData, config: &PoseConfig) -> StaticPose {
+    let palm_center = hand.palm_center();
+    let is_extended = |finger: &Finger| finger_extension(finger) > config.extended_threshold;
+    let is_curled = |finger: &Finger| finger_curl(finger, palm_center) > config.curled_threshold;
+
+    let thumb_out =
+        relative_pinch_distance(hand, hand.index[3]).is_some_and(|distance| distance > config.thumb_out_distance);
+    let thumb_touches_index =
+        relative_pinch_distance(hand, hand.index[0]).is_some_and(|distance| distance < config.ok_touch_distance);
+
+    let fingers = [&hand.index, &hand.middle, &hand.ring, &hand.pinky];
+    let extended = fingers.map(is_extended);
+    let curled = fingers.map(is_curled);
+
+    if thumb_touches_index && extended[1..].iter().all(|extended| *extended) {
+        return StaticPose::Ok;
+    }
+
+    match (extended, curled, thumb_out) {
+        ([true, true, true, true], _, true) => StaticPose::OpenPalm,
+        (_, [true, true, true, true], true) => StaticPose::ThumbsUp,
+        (_, [true, true, true, true], false) => StaticPose::Fist,
+        ([true, _, _, _], [_, true, true, true], true) => StaticPose::FingerGun,
+        ([true, _, _, _], [_, true, true, true], false) => StaticPose::Point,
+        ([true, true, _, _], [_, _, true, true], _) => StaticPose::Peace,
+        ([true, _, _, true], [_, true, true, _], _) => StaticPose::Rock,
+        _ => StaticPose::Unknown,
+    }
+}
+
+/// Detector of [`PoseChanged`]; classifies the newest frame of the hand and reports a new pose once it was held
+/// for [`PoseConfig::min_pose_duration`].
+pub struct PoseDetector;
+
+impl Gesture for PoseDetector {
+    const NAME: &'static str = "pose";
+    type Event = PoseChanged;
+    type Config = PoseConfig;
+    type HandState = PoseState;
+
+    fn update(
+        config: &PoseConfig,
+        state: &mut PoseState,
+        hand: HandType,
+        _history: &HandHistory,
+        new_samples: &[&HandSample],
+    ) -> Option<PoseChanged> {
+        let sample = new_samples.last()?;
+        let pose = classify_pose(&sample.hand, config);
+        if pose == state.pose {
+            state.candidate = None;
+            return None;
+        }
+
+        let since = match state.candidate {
+            Some((candidate, since)) if candidate == pose => since,
+            _ => {
+                state.candidate = Some((pose, sample.timestamp));
+                sample.timestamp
+            }
+        };
+        if sample.timestamp - since < as_micros(config.min_pose_duration) {
+            return None;
+        }
+
+        let event = PoseChanged {
+            hand,
+            from: state.pose,
+            to: pose,
+        };
+        state.pose = pose;
+        state.candidate = None;
+        Some(event)
+    }
+
+    fn hand_lost(_config: &PoseConfig, state: PoseState, hand: HandType) -> Option<PoseChanged> {
+        (state.pose != StaticPose::Unknown).then_some(PoseChanged {
+            hand,
+            from: state.pose,
+            to: StaticPose::Unknown,
+        })
+    }
+}
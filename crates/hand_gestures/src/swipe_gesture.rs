@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use bevy::math::Vec3;
+use bevy::prelude::{Event, Resource};
+
+use crate::gesture::Gesture;
+use crate::models::HandType;
+use crate::{as_micros, HandHistory, HandSample};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+    /// Along -Z, i.e. away from the user.
+    TowardScreen,
+    /// Along +Z, i.e. towards the user.
+    AwayFromScreen,
+}
+
+impl SwipeDirection {
+    fn from_vector(vector: Vec3) -> Self {
+        let abs = vector.abs();
+        if abs.x >= abs.y && abs.x >= abs.z {
+            if vector.x < 0.0 {
+                SwipeDirection::Left
+            } else {
+                SwipeDirection::Right
+            }
+        } else if abs.y >= abs.z {
+            if vector.y < 0.0 {
+                SwipeDirection::Down
+            } else {
+                SwipeDirection::Up
+            }
+        } else if vector.z < 0.0 {
+            SwipeDirection::TowardScreen
+        } else {
+            SwipeDirection::AwayFromScreen
+        }
+    }
+}
+
+/// Thresholds of swipe detection; distances are relative to the hand size (see [`crate::models::HandData::size`]).
+#[derive(Resource, Debug, Clone)]
+pub struct SwipeConfig {
+    /// Palm movements from this period are analyzed.
+    pub window: Duration,
+    /// Shortest distance the palm has to move within the window.
+    pub min_distance: f32,
+    /// Lowest average speed of the palm, in hand sizes per second.
+    pub min_speed: f32,
+    /// Smallest share of the movement along the main axis; lower values accept more diagonal swipes.
+    pub min_straightness: f32,
+    /// Time after a swipe in which the hand cannot swipe again.
+    pub cooldown: Duration,
+}
+
+impl Default for SwipeConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_millis(300),
+            min_distance: 3.0,
+            min_speed: 15.0,
+            min_straightness: 0.8,
+            cooldown: Duration::from_millis(600),
+        }
+    }
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct SwipeGesture {
+    pub hand: HandType,
+    pub direction: SwipeDirection,
+    /// average speed of the palm, in tracker units per second
+    pub speed: f32,
+    /// palm position at the beginning of the swipe
+    pub start: Vec3,
+    /// palm position at the end of the swipe
+    pub end: Vec3,
+}
+
+/// Detector of [`SwipeGesture`]; checks if the palm moved fast enough along one of the axes
+/// within [`SwipeConfig::window`].
+pub struct SwipeDetector;
+
+#[derive(Default)]
+pub struct SwipeState {
+    /// Timestamp of the last swipe, in microseconds.
+    last_swipe: Option<i64>,
+}
+
+impl Gesture for SwipeDetector {
+    const NAME: &'static str = "swipe";
+    type Event = SwipeGesture;
+    type Config = SwipeConfig;
+    type HandState = SwipeState;
+
+    fn update(
+        config: &SwipeConfig,
+        state: &mut SwipeState,
+        hand: HandType,
+        history: &HandHistory,
+        _new_samples: &[&HandSample],
+    ) -> Option<SwipeGesture> {
+        let mut samples = history.samples_within(config.window);
+        let (first, last) = (samples.next()?, samples.last()?);
+
+        // frames from before the cooldown end are skipped, so one movement is not reported twice
+        if state.last_swipe.is_some_and(|timestamp| {
+            last.timestamp - timestamp < as_micros(config.cooldown) || first.timestamp <= timestamp
+        }) {
+            return None;
+        }
+
+        let hand_size = last.hand.size();
+        let duration = (last.timestamp - first.timestamp) as f32 / 1_000_000.0;
+        if hand_size <= f32::EPSILON || duration <= 0.0 {
+            return None;
+        }
+
+        let start = first.hand.palm_center();
+        let end = last.hand.palm_center();
+        let movement = end - start;
+        let distance = movement.length();
+        let speed = distance / duration;
+        if distance < config.min_distance * hand_size || speed < config.min_speed * hand_size {
+            return None;
+        }
+
+        let main_axis_share = movement.abs().max_element() / distance;
+        if main_axis_share < config.min_straightness {
+            return None;
+        }
+
+        state.last_swipe = Some(last.timestamp);
+        Some(SwipeGesture {
+            hand,
+            direction: SwipeDirection::from_vector(movement),
+            speed,
+            start,
+            end,
+        })
+    }
+}
@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+use bevy::math::Vec3;
+use bevy::prelude::{Event, Resource};
+
+use crate::gesture::Gesture;
+use crate::models::HandType;
+use crate::{as_micros, HandHistory, HandSample};
+
+/// Thresholds of tap detection; distances are relative to the hand size (see [`crate::models::HandData::size`]).
+#[derive(Resource, Debug, Clone)]
+pub struct TapConfig {
+    /// Index finger tip movements from this period are analyzed; the whole tap has to fit in it.
+    pub window: Duration,
+    /// Shortest distance the tip has to move toward the screen.
+    pub min_depth: f32,
+    /// Lowest average speed of the tip moving toward the screen, in hand sizes per second.
+    pub min_speed: f32,
+    /// Part of the forward distance the tip has to move back, for the tap to end.
+    pub min_return: f32,
+    /// Largest sideways movement of the tip, relative to the forward distance;
+    /// higher values accept more diagonal movements.
+    pub max_sideways: f32,
+    /// Time after a tap in which the hand cannot tap again.
+    pub cooldown: Duration,
+}
+
+impl Default for TapConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_millis(500),
+            min_depth: 0.5,
+            min_speed: 4.0,
+            min_return: 0.5,
+            max_sideways: 0.7,
+            cooldown: Duration::from_millis(300),
+        }
+    }
+}
+
+/// Short poke of the index finger tip toward the screen (along -Z) and back, e.g. to click in a hand tracked UI.
+#[derive(Event, Debug, Clone)]
+pub struct TapGesture {
+    pub hand: HandType,
+    /// the index finger tip, when it was closest to the screen
+    pub position: Vec3,
+}
+
+/// Detector of [`TapGesture`]; checks if the index finger tip moved quickly toward the screen
+/// and back within [`TapConfig::window`].
+/// Example depths of the tip for tap gesture (oldest -> newest):
+/// [100, 99, 92, 84, 80, 83, 90, 95]
+pub struct TapDetector;
+
+#[derive(Default)]
+pub struct TapState {
+    /// Timestamp of the last tap, in microseconds.
+    last_tap: Option<i64>,
+}
+
+impl Gesture for TapDetector {
+    const NAME: &'static str = "tap";
+    type Event = TapGesture;
+    type Config = TapConfig;
+    type HandState = TapState;
+
+    fn update(
+        config: &TapConfig,
+        state: &mut TapState,
+        hand: HandType,
+        history: &HandHistory,
+        _new_samples: &[&HandSample],
+    ) -> Option<TapGesture> {
+        // frames of the last tap are skipped, so one movement is not reported twice
+        let skipped_until = state.last_tap.unwrap_or(i64::MIN);
+        let samples = history
+            .samples_within(config.window)
+            .filter(|sample| sample.timestamp > skipped_until)
+            .collect::<Vec<_>>();
+        let last = samples.last()?;
+        if state
+            .last_tap
+            .is_some_and(|timestamp| last.timestamp - timestamp < as_micros(config.cooldown))
+        {
+            return None;
+        }
+
+        let position = find_tap(&samples, config)?;
+        state.last_tap = Some(last.timestamp);
+        Some(TapGesture { hand, position })
+    }
+}
+
+/// Returns the deepest tip position of a tap found in `samples`, oldest first.
+fn find_tap(samples: &[&HandSample], config: &TapConfig) -> Option<Vec3> {
+    let tip = |sample: &HandSample| sample.hand.index[0];
+
+    let (deepest_index, deepest) = samples
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| tip(a).z.total_cmp(&tip(b).z))?;
+    let start = farthest_from_screen(&samples[..deepest_index])?;
+    let end = farthest_from_screen(&samples[deepest_index + 1..])?;
+
+    let hand_size = deepest.hand.size();
+    let duration = (deepest.timestamp - start.timestamp) as f32 / 1_000_000.0;
+    if hand_size <= f32::EPSILON || duration <= 0.0 {
+        return None;
+    }
+
+    let forward = tip(start) - tip(deepest);
+    let depth = forward.z;
+    let sideways = forward.truncate().length();
+    let returned = tip(end).z - tip(deepest).z;
+    if depth < config.min_depth * hand_size
+        || depth / duration < config.min_speed * hand_size
+        || returned < config.min_return * depth
+        || sideways > config.max_sideways * depth
+    {
+        return None;
+    }
+
+    Some(tip(deepest))
+}
+
+fn farthest_from_screen<'a>(samples: &[&'a HandSample]) -> Option<&'a HandSample> {
+    samples
+        .iter()
+        .copied()
+        .max_by(|a, b| a.hand.index[0].z.total_cmp(&b.hand.index[0].z))
+}
@@ -0,0 +1,100 @@
+use bevy::math::{Quat, Vec3};
+use bevy::prelude::{Event, EventWriter, Res, ResMut, Resource};
+
+use crate::models::HandType;
+use crate::pinch_gesture::{PinchFinger, PinchGestureInfo};
+
+/// Both hands started pinching with index fingers.
+#[derive(Event, Debug, Clone)]
+pub struct TwoHandPinchStarted {
+    /// the point between both pinches
+    pub center: Vec3,
+}
+
+/// Sent once per app update while both hands are pinching; values are changes since the previous update,
+/// so they can be applied directly to a transform, e.g. of a camera or a shape.
+#[derive(Event, Debug, Clone)]
+pub struct TwoHandPinch {
+    /// the point between both pinches
+    pub center: Vec3,
+    /// ratio of the current and the previous distance between pinches
+    pub scale: f32,
+    /// rotation of the axis going from the left to the right pinch
+    pub rotation: Quat,
+    /// movement of the center
+    pub translation: Vec3,
+}
+
+/// One of the hands stopped pinching.
+#[derive(Event, Debug, Clone)]
+pub struct TwoHandPinchEnded;
+
+#[derive(Resource, Default)]
+pub struct TwoHandPinchInfo {
+    /// Positions of the left and the right pinch in the previous update.
+    previous_pinches: Option<(Vec3, Vec3)>,
+}
+
+impl TwoHandPinchInfo {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.previous_pinches.is_some()
+    }
+}
+
+/// Finding 'two hand pinch', i.e. a period in which both hands pinch with index fingers.
+pub fn detect_two_hand_pinch(
+    pinch_gesture_info: Res<PinchGestureInfo>,
+    mut two_hand_pinch_info: ResMut<TwoHandPinchInfo>,
+    mut two_hand_pinch_started: EventWriter<TwoHandPinchStarted>,
+    mut two_hand_pinch: EventWriter<TwoHandPinch>,
+    mut two_hand_pinch_ended: EventWriter<TwoHandPinchEnded>,
+) {
+    let pinch_position = |hand_type| {
+        pinch_gesture_info
+            .pinch_transform(hand_type, PinchFinger::Index)
+            .map(|transform| transform.translation)
+    };
+    let pinches = pinch_position(HandType::Left).zip(pinch_position(HandType::Right));
+
+    match (two_hand_pinch_info.previous_pinches, pinches) {
+        (None, Some((left, right))) => {
+            two_hand_pinch_started.send(TwoHandPinchStarted {
+                center: left.lerp(right, 0.5),
+            });
+        }
+        (Some((previous_left, previous_right)), Some((left, right))) => {
+            let previous_axis = previous_right - previous_left;
+            let axis = right - left;
+            let previous_center = previous_left.lerp(previous_right, 0.5);
+            let center = left.lerp(right, 0.5);
+
+            let previous_distance = previous_axis.length();
+            let scale = if previous_distance > f32::EPSILON {
+                axis.length() / previous_distance
+            } else {
+                1.0
+            };
+            let rotation = match (previous_axis.try_normalize(), axis.try_normalize()) {
+                (Some(from), Some(to)) => Quat::from_rotation_arc(from, to),
+                _ => Quat::IDENTITY,
+            };
+
+            two_hand_pinch.send(TwoHandPinch {
+                center,
+                scale,
+                rotation,
+                translation: center - previous_center,
+            });
+        }
+        (Some(_), None) => {
+            two_hand_pinch_ended.send(TwoHandPinchEnded);
+        }
+        (None, None) => {}
+    }
+
+    two_hand_pinch_info.previous_pinches = pinches;
+}
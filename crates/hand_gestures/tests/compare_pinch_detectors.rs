@@ -0,0 +1,86 @@
+//! Compares detectors of pinches on recorded sessions:
+//! - single threshold: [`PinchDetector`] with equal engage and release thresholds and no minimum hold, which is how
+//!   the newest frame was checked before the detector was added,
+//! - hysteresis: [`PinchDetector`] with the default [`PinchConfig`].
+//!
+//! The baseline `analyze_hand_data`, which scanned the whole history for a below/above/below pattern, is not compared:
+//! its normalized distance never exceeds 15 / 70, so it did not find a pinch in any recording.
+//!
+//! No recording of a real tracker is available, so the fixtures are 3 s recordings of the default animation of
+//! `synthetic_input::ScriptedHandSource`: the right hand pinches once, while the left one closes and points.
+//! `pinch_session_jittered.jsonl` was recorded with `.with_jitter(2.5, 5)`.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use hand_gestures::models::HandType;
+use hand_gestures::pinch_calibration::PinchCalibration;
+use hand_gestures::pinch_gesture::{pinch_strength, PinchConfig, PinchDetector, PinchFinger, PinchTransition};
+use hand_gestures::recording::read_recording;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn single_threshold() -> PinchConfig {
+    let engage_threshold = PinchConfig::default().engage_threshold;
+    PinchConfig {
+        engage_threshold,
+        release_threshold: engage_threshold,
+        min_hold: Duration::ZERO,
+    }
+}
+
+/// Runs a detector for every finger of both hands over the recording and returns the started pinches.
+fn started_pinches(recording: &str, config: &PinchConfig) -> Vec<(HandType, PinchFinger)> {
+    let frames = read_recording(fixture(recording)).expect("fixture is readable");
+    let calibration = PinchCalibration::default();
+    let mut detectors = [HandType::Left, HandType::Right]
+        .map(|hand_type| PinchFinger::ALL.map(|finger| (hand_type, finger, PinchDetector::default())));
+
+    let mut started = Vec::new();
+    for frame in &frames {
+        for (hand_type, finger, detector) in detectors.iter_mut().flatten() {
+            let Some(hand) = frame.hands.iter().flatten().find(|hand| hand.type_ == *hand_type) else {
+                detector.release();
+                continue;
+            };
+            let strength = pinch_strength(hand, *finger, &calibration);
+            if detector.update(config, frame.timestamp, strength) == Some(PinchTransition::Started) {
+                started.push((*hand_type, *finger));
+            }
+        }
+    }
+    started
+}
+
+#[test]
+fn both_detectors_find_the_pinch_without_noise() {
+    let expected = [(HandType::Right, PinchFinger::Index)];
+
+    assert_eq!(started_pinches("pinch_session.jsonl", &single_threshold()), expected);
+    assert_eq!(
+        started_pinches("pinch_session.jsonl", &PinchConfig::default()),
+        expected
+    );
+}
+
+#[test]
+fn only_hysteresis_ignores_jitter() {
+    // noise of the left index finger moving towards the thumb crosses the single threshold three times
+    assert_eq!(
+        started_pinches("pinch_session_jittered.jsonl", &single_threshold()),
+        [
+            (HandType::Left, PinchFinger::Index),
+            (HandType::Right, PinchFinger::Index),
+            (HandType::Left, PinchFinger::Index),
+            (HandType::Left, PinchFinger::Index)
+        ]
+    );
+    assert_eq!(
+        started_pinches("pinch_session_jittered.jsonl", &PinchConfig::default()),
+        [(HandType::Right, PinchFinger::Index)]
+    );
+}
@@ -0,0 +1,389 @@
+use std::sync::mpsc::{channel, Receiver, SendError, Sender};
+use std::thread;
+use std::time::Duration;
+
+use bevy::log::warn;
+use bevy::prelude::{Event, EventWriter, NonSend, ResMut, Resource};
+use hand_gestures::source::{HandTrackingSource, TrackingFrame};
+
+/// State of the connection with a tracking service, updated by [`update_connection_state`].
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrackingConnectionState {
+    #[default]
+    Disconnected,
+    Connecting {
+        attempt: u32,
+    },
+    /// Connection attempt failed, next one starts after `delay`.
+    WaitingToRetry {
+        attempt: u32,
+        delay: Duration,
+    },
+    Connected,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct Connected;
+
+#[derive(Event, Debug, Clone)]
+pub struct Disconnected;
+
+#[derive(Event, Debug, Clone)]
+pub struct DeviceAttached {
+    pub device_id: u32,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct DeviceLost {
+    pub device_id: u32,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct DeviceFailure {
+    pub message: String,
+}
+
+/// A connection attempt or an open connection failed; it is retried after
+/// [`TrackingConnectionState::WaitingToRetry`].
+#[derive(Event, Debug, Clone)]
+pub struct ConnectionFailed {
+    pub attempt: u32,
+    pub error: String,
+}
+
+/// Event reported by a [`TrackerConnection`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionEvent {
+    Connected,
+    Disconnected,
+    DeviceAttached { device_id: u32 },
+    DeviceLost { device_id: u32 },
+    DeviceFailure { message: String },
+}
+
+pub enum PollResult {
+    /// Boxed, as a frame is much larger than the other results.
+    Frame(Box<TrackingFrame>),
+    Event(ConnectionEvent),
+    /// Timeout or a message which is not interesting for the app.
+    Nothing,
+}
+
+/// Connection with a tracking service, e.g. LeapC. Abstracted, so reconnecting can be exercised
+/// with a mock connection.
+pub trait TrackerConnection {
+    /// Waits up to `timeout` for the next message. An error closes the connection and
+    /// starts reconnecting.
+    fn poll(&mut self, timeout: Duration) -> Result<PollResult, String>;
+}
+
+/// Delays between consecutive connection attempts.
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f32,
+}
+
+impl Backoff {
+    /// Returns delay after the failed `attempt`, counting from 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16) as i32;
+        self.initial_delay
+            .mul_f32(self.multiplier.powi(exponent))
+            .min(self.max_delay)
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            multiplier: 2.0,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum StatusMessage {
+    State(TrackingConnectionState),
+    Event(ConnectionEvent),
+    Failed { attempt: u32, error: String },
+}
+
+/// [`HandTrackingSource`] which runs a [`TrackerConnection`] on a dedicated thread.
+///
+/// The thread drains all messages of the connection, sends frames through a channel and
+/// reconnects with [`Backoff`] when the connection fails, so the render loop is never blocked.
+pub struct ConnectionSource {
+    frames: Receiver<TrackingFrame>,
+    status: Receiver<StatusMessage>,
+}
+
+impl ConnectionSource {
+    /// Spawns the polling thread; `connect` is called on it for every connection attempt,
+    /// so the connection itself does not need to be `Send`.
+    pub fn spawn<C, F>(name: &str, connect: F, backoff: Backoff, poll_timeout: Duration) -> Self
+    where
+        C: TrackerConnection,
+        F: FnMut() -> Result<C, String> + Send + 'static,
+    {
+        let (frames_sender, frames) = channel();
+        let (status_sender, status) = channel();
+
+        thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                // stops once the source is dropped
+                let _ = run_connection(connect, backoff, poll_timeout, &frames_sender, &status_sender);
+            })
+            .expect("Failed to spawn the connection thread");
+
+        Self { frames, status }
+    }
+}
+
+impl HandTrackingSource for ConnectionSource {
+    fn poll_frames(&mut self, _delta: Duration) -> Vec<TrackingFrame> {
+        self.frames.try_iter().collect()
+    }
+}
+
+fn run_connection<C, F>(
+    mut connect: F,
+    backoff: Backoff,
+    poll_timeout: Duration,
+    frames: &Sender<TrackingFrame>,
+    status: &Sender<StatusMessage>,
+) -> Result<(), SendError<StatusMessage>>
+where
+    C: TrackerConnection,
+    F: FnMut() -> Result<C, String>,
+{
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        status.send(StatusMessage::State(TrackingConnectionState::Connecting { attempt }))?;
+
+        match connect() {
+            Ok(mut connection) => {
+                let mut connected = false;
+                loop {
+                    match connection.poll(poll_timeout) {
+                        Ok(PollResult::Frame(frame)) => {
+                            if frames.send(*frame).is_err() {
+                                return Ok(());
+                            }
+                        }
+                        Ok(PollResult::Event(event)) => {
+                            match event {
+                                ConnectionEvent::Connected => {
+                                    // a failure of this session is reported as the first failed attempt
+                                    attempt = 1;
+                                    connected = true;
+                                    status.send(StatusMessage::State(TrackingConnectionState::Connected))?;
+                                }
+                                ConnectionEvent::Disconnected => {
+                                    connected = false;
+                                    status.send(StatusMessage::State(TrackingConnectionState::Disconnected))?;
+                                }
+                                _ => {}
+                            }
+                            status.send(StatusMessage::Event(event))?;
+                        }
+                        Ok(PollResult::Nothing) => {}
+                        Err(error) => {
+                            status.send(StatusMessage::Failed {
+                                attempt,
+                                error: format!("tracking connection failed: {error}"),
+                            })?;
+                            break;
+                        }
+                    }
+                }
+
+                if connected {
+                    status.send(StatusMessage::Event(ConnectionEvent::Disconnected))?;
+                }
+            }
+            Err(error) => status.send(StatusMessage::Failed {
+                attempt,
+                error: format!("failed to connect to the tracking service: {error}"),
+            })?,
+        }
+
+        let delay = backoff.delay(attempt);
+        status.send(StatusMessage::State(TrackingConnectionState::WaitingToRetry {
+            attempt,
+            delay,
+        }))?;
+        thread::sleep(delay);
+    }
+}
+
+/// Moves connection state changes and events from [`ConnectionSource`] to the app.
+#[allow(clippy::too_many_arguments)]
+pub fn update_connection_state(
+    source: NonSend<ConnectionSource>,
+    mut state: ResMut<TrackingConnectionState>,
+    mut connected: EventWriter<Connected>,
+    mut disconnected: EventWriter<Disconnected>,
+    mut device_attached: EventWriter<DeviceAttached>,
+    mut device_lost: EventWriter<DeviceLost>,
+    mut device_failure: EventWriter<DeviceFailure>,
+    mut connection_failed: EventWriter<ConnectionFailed>,
+) {
+    for message in source.status.try_iter() {
+        match message {
+            StatusMessage::State(new_state) => *state = new_state,
+            StatusMessage::Event(ConnectionEvent::Connected) => {
+                connected.send(Connected);
+            }
+            StatusMessage::Event(ConnectionEvent::Disconnected) => {
+                disconnected.send(Disconnected);
+            }
+            StatusMessage::Event(ConnectionEvent::DeviceAttached { device_id }) => {
+                device_attached.send(DeviceAttached { device_id });
+            }
+            StatusMessage::Event(ConnectionEvent::DeviceLost { device_id }) => {
+                device_lost.send(DeviceLost { device_id });
+            }
+            StatusMessage::Event(ConnectionEvent::DeviceFailure { message }) => {
+                device_failure.send(DeviceFailure { message });
+            }
+            StatusMessage::Failed { attempt, error } => {
+                warn!("{error}");
+                connection_failed.send(ConnectionFailed { attempt, error });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+
+    /// Connection returning scripted poll results; fails once they run out.
+    struct MockConnection {
+        results: VecDeque<PollResult>,
+    }
+
+    impl TrackerConnection for MockConnection {
+        fn poll(&mut self, _timeout: Duration) -> Result<PollResult, String> {
+            self.results.pop_front().ok_or_else(|| "connection closed".to_string())
+        }
+    }
+
+    const BACKOFF: Backoff = Backoff {
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(4),
+        multiplier: 2.0,
+    };
+
+    /// Runs [`run_connection`] with connections returned by `connect` and returns its first `count` messages.
+    fn status_messages<F>(connect: F, count: usize) -> Vec<StatusMessage>
+    where
+        F: FnMut() -> Result<MockConnection, String> + Send + 'static,
+    {
+        let (frames_sender, _frames) = channel();
+        let (status_sender, status) = channel();
+        thread::spawn(move || run_connection(connect, BACKOFF, Duration::ZERO, &frames_sender, &status_sender));
+
+        // the thread stops once the receivers are dropped
+        status.iter().take(count).collect()
+    }
+
+    fn state(state: TrackingConnectionState) -> StatusMessage {
+        StatusMessage::State(state)
+    }
+
+    fn failed(attempt: u32, error: &str) -> StatusMessage {
+        StatusMessage::Failed {
+            attempt,
+            error: error.to_string(),
+        }
+    }
+
+    #[test]
+    fn backoff_delay_grows_up_to_max_delay() {
+        let backoff = Backoff::default();
+
+        assert_eq!(backoff.delay(1), Duration::from_millis(500));
+        assert_eq!(backoff.delay(2), Duration::from_secs(1));
+        assert_eq!(backoff.delay(3), Duration::from_secs(2));
+        assert_eq!(backoff.delay(5), Duration::from_secs(8));
+        assert_eq!(backoff.delay(6), Duration::from_secs(10));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn failed_attempts_are_retried_with_growing_delay() {
+        let messages = status_messages(|| Err("service not running".to_string()), 9);
+        let error = "failed to connect to the tracking service: service not running";
+
+        assert_eq!(
+            messages,
+            [
+                state(TrackingConnectionState::Connecting { attempt: 1 }),
+                failed(1, error),
+                state(TrackingConnectionState::WaitingToRetry {
+                    attempt: 1,
+                    delay: BACKOFF.delay(1),
+                }),
+                state(TrackingConnectionState::Connecting { attempt: 2 }),
+                failed(2, error),
+                state(TrackingConnectionState::WaitingToRetry {
+                    attempt: 2,
+                    delay: BACKOFF.delay(2),
+                }),
+                state(TrackingConnectionState::Connecting { attempt: 3 }),
+                failed(3, error),
+                state(TrackingConnectionState::WaitingToRetry {
+                    attempt: 3,
+                    delay: BACKOFF.delay(3),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_session_reports_disconnected_and_restarts_attempts() {
+        let mut attempts = 0;
+        let connect = move || {
+            attempts += 1;
+            if attempts == 1 {
+                return Err("service not running".to_string());
+            }
+            Ok(MockConnection {
+                results: VecDeque::from([PollResult::Nothing, PollResult::Event(ConnectionEvent::Connected)]),
+            })
+        };
+
+        let messages = status_messages(connect, 10);
+
+        assert_eq!(
+            messages,
+            [
+                state(TrackingConnectionState::Connecting { attempt: 1 }),
+                failed(1, "failed to connect to the tracking service: service not running"),
+                state(TrackingConnectionState::WaitingToRetry {
+                    attempt: 1,
+                    delay: BACKOFF.delay(1),
+                }),
+                state(TrackingConnectionState::Connecting { attempt: 2 }),
+                state(TrackingConnectionState::Connected),
+                StatusMessage::Event(ConnectionEvent::Connected),
+                failed(1, "tracking connection failed: connection closed"),
+                StatusMessage::Event(ConnectionEvent::Disconnected),
+                state(TrackingConnectionState::WaitingToRetry {
+                    attempt: 1,
+                    delay: BACKOFF.delay(1),
+                }),
+                state(TrackingConnectionState::Connecting { attempt: 2 }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use std::time::Duration;
+
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::math::Vec3;
+use bevy::prelude::{IntoSystemConfigs, World};
+use hand_gestures::models::{Arm, Finger, HandData, HandType, Palm};
+use hand_gestures::source::{update_hands_data, TrackingFrame, UpdateHandsData};
+use leaprs::{
+    BoneRef, Connection, ConnectionConfig, DigitRef, Error as LeapError, EventRef, HandRef, HandType as LeapHandType,
+};
+
+use crate::connection::{
+    update_connection_state, Backoff, Connected, ConnectionEvent, ConnectionFailed, ConnectionSource, DeviceAttached,
+    DeviceFailure, DeviceLost, Disconnected, PollResult, TrackerConnection, TrackingConnectionState,
+};
+
+const POLL_TIMEOUT: Duration = Duration::from_millis(50);
+
+pub struct LeapInputPlugin;
+
+impl Plugin for LeapInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TrackingConnectionState>()
+            .add_event::<Connected>()
+            .add_event::<Disconnected>()
+            .add_event::<DeviceAttached>()
+            .add_event::<DeviceLost>()
+            .add_event::<DeviceFailure>()
+            .add_event::<ConnectionFailed>()
+            .add_systems(Startup, spawn_connection)
+            .add_systems(
+                Update,
+                (update_connection_state, update_hands_data::<ConnectionSource>).in_set(UpdateHandsData),
+            );
+    }
+}
+
+/// [`TrackerConnection`] to the LeapC service.
+struct LeapConnection {
+    connection: Connection,
+}
+
+impl LeapConnection {
+    fn open() -> Result<Self, String> {
+        let mut connection = Connection::create(ConnectionConfig::default())
+            .map_err(|e| format!("failed to create connection: {e:?}"))?;
+
+        connection
+            .open()
+            .map_err(|e| format!("failed to open the connection: {e:?}"))?;
+
+        Ok(Self { connection })
+    }
+}
+
+impl TrackerConnection for LeapConnection {
+    fn poll(&mut self, timeout: Duration) -> Result<PollResult, String> {
+        let message = match self.connection.poll(timeout.as_millis() as u32) {
+            Ok(message) => message,
+            Err(LeapError::Timeout) => return Ok(PollResult::Nothing),
+            Err(e) => return Err(format!("{e:?}")),
+        };
+
+        let result = match &message.event() {
+            EventRef::Connection(_) => PollResult::Event(ConnectionEvent::Connected),
+            EventRef::ConnectionLost(_) => PollResult::Event(ConnectionEvent::Disconnected),
+            EventRef::Device(e) => PollResult::Event(ConnectionEvent::DeviceAttached {
+                device_id: e.device().id,
+            }),
+            EventRef::DeviceLost(e) => PollResult::Event(ConnectionEvent::DeviceLost {
+                device_id: e.device().id,
+            }),
+            EventRef::DeviceFailure(e) => PollResult::Event(ConnectionEvent::DeviceFailure {
+                message: format!("device status {:#x}", e.status),
+            }),
+            EventRef::Tracking(e) => {
+                let hand1 = e.hands().get(0).map(map_from_leap_hand);
+                let hand2 = e.hands().get(1).map(map_from_leap_hand);
+
+                PollResult::Frame(Box::new(TrackingFrame {
+                    frame_id: e.tracking_frame_id,
+                    timestamp: e.info.timestamp,
+                    hands: [hand1, hand2],
+                }))
+            }
+            _ => PollResult::Nothing,
+        };
+
+        Ok(result)
+    }
+}
+
+fn spawn_connection(world: &mut World) {
+    let source = ConnectionSource::spawn("leap_polling", LeapConnection::open, Backoff::default(), POLL_TIMEOUT);
+
+    world.insert_non_send_resource(source);
+}
+
+fn map_from_leap_hand(leap_hand: &HandRef) -> HandData {
+    let palm = leap_hand.palm();
+    let arm = leap_hand.arm();
+
+    HandData {
+        id: Some(leap_hand.id),
+        type_: match leap_hand.hand_type() {
+            LeapHandType::Left => HandType::Left,
+            LeapHandType::Right => HandType::Right,
+        },
+        confidence: leap_hand.confidence,
+        thumb: get_simplified_finger(leap_hand.thumb()),
+        index: get_simplified_finger(leap_hand.index()),
+        middle: get_simplified_finger(leap_hand.middle()),
+        ring: get_simplified_finger(leap_hand.ring()),
+        pinky: get_simplified_finger(leap_hand.pinky()),
+        palm: Some(Palm {
+            position: Vec3::from_array(palm.position().array()),
+            normal: Vec3::from_array(palm.normal().array()),
+            direction: Vec3::from_array(palm.direction().array()),
+            velocity: Vec3::from_array(palm.velocity().array()),
+            width: palm.width,
+        }),
+        arm: Some(Arm {
+            wrist: Vec3::from_array(arm.next_joint().array()),
+            elbow: Vec3::from_array(arm.prev_joint().array()),
+            width: arm.width,
+        }),
+        grab_strength: Some(leap_hand.grab_strength),
+        pinch_strength: Some(leap_hand.pinch_strength),
+    }
+}
+
+fn get_bones<'a>(digit: &'a DigitRef<'a>) -> [BoneRef<'a>; 4] {
+    [
+        digit.distal(),
+        digit.intermediate(),
+        digit.proximal(),
+        digit.metacarpal(),
+    ]
+}
+
+fn get_simplified_finger(digit: DigitRef) -> Finger {
+    let bones = get_bones(&digit);
+    [
+        Vec3::from_array(bones[0].next_joint().array()),
+        Vec3::from_array(bones[0].prev_joint().array()),
+        Vec3::from_array(bones[1].prev_joint().array()),
+        Vec3::from_array(bones[2].prev_joint().array()),
+        Vec3::from_array(bones[3].prev_joint().array()),
+    ]
+}
@@ -1,3 +1,4 @@
+#[cfg(feature = "leap")]
 pub extern crate leaprs;
 
 use bevy::app::{App, Plugin, Startup};
@@ -5,14 +6,20 @@ use bevy::asset::Assets;
 use bevy::hierarchy::BuildChildren;
 use bevy::pbr::{PbrBundle, StandardMaterial};
 use bevy::prelude::*;
-use leaprs::{Connection, ConnectionConfig};
 
-pub struct LeapInputPlugin;
+#[cfg(feature = "leap")]
+pub use crate::leap_source::LeapInputPlugin;
 
-impl Plugin for LeapInputPlugin {
+pub mod connection;
+#[cfg(feature = "leap")]
+mod leap_source;
+
+/// Spawns joints and phalanges used to render the tracked hands.
+pub struct HandVisualsPlugin;
+
+impl Plugin for HandVisualsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, create_connection)
-            .add_systems(Startup, setup);
+        app.add_systems(Startup, setup);
     }
 }
 
@@ -66,11 +73,3 @@ fn setup(
             }
         });
 }
-
-fn create_connection(world: &mut World) {
-    let mut connection = Connection::create(ConnectionConfig::default()).expect("Failed to create connection");
-
-    connection.open().expect("Failed to open the connection");
-
-    world.insert_non_send_resource(connection);
-}
@@ -0,0 +1,118 @@
+use bevy::math::{Quat, Vec3};
+use hand_gestures::models::{HandData, HandType};
+
+use crate::pose::HandPose;
+
+/// Time step used to compute the palm velocity, in seconds.
+const VELOCITY_SAMPLE_INTERVAL: f32 = 0.01;
+
+#[derive(Copy, Clone, Debug)]
+pub struct HandKeyframe {
+    /// Time of the keyframe in seconds, since the start of an animation.
+    pub time: f32,
+    pub pose: HandPose,
+    /// Position of the palm center.
+    pub position: Vec3,
+    pub rotation: Quat,
+}
+
+/// Keyframed animation of a single hand. Poses and transforms are interpolated linearly between keyframes.
+#[derive(Clone, Debug)]
+pub struct HandAnimation {
+    /// Reported as [`HandData::id`].
+    pub id: u32,
+    pub hand_type: HandType,
+    pub confidence: f32,
+    pub looping: bool,
+    keyframes: Vec<HandKeyframe>,
+}
+
+impl HandAnimation {
+    pub fn new(hand_type: HandType) -> Self {
+        Self {
+            id: match hand_type {
+                HandType::Left => 1,
+                HandType::Right => 2,
+            },
+            hand_type,
+            confidence: 1.0,
+            looping: true,
+            keyframes: Vec::new(),
+        }
+    }
+
+    pub fn with_id(mut self, id: u32) -> Self {
+        self.id = id;
+        self
+    }
+
+    pub fn with_confidence(mut self, confidence: f32) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    pub fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Adds a keyframe; keyframes can be added in any order.
+    pub fn with_keyframe(self, time: f32, pose: HandPose, position: Vec3) -> Self {
+        self.with_rotated_keyframe(time, pose, position, Quat::IDENTITY)
+    }
+
+    pub fn with_rotated_keyframe(mut self, time: f32, pose: HandPose, position: Vec3, rotation: Quat) -> Self {
+        self.keyframes.push(HandKeyframe {
+            time,
+            pose,
+            position,
+            rotation,
+        });
+        self.keyframes.sort_by(|k1, k2| k1.time.total_cmp(&k2.time));
+        self
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    /// Returns the hand at `time` seconds, or `None` if the animation has no keyframes.
+    pub fn sample(&self, time: f32) -> Option<HandData> {
+        let (pose, position, rotation) = self.transform_at(time)?;
+        let (_, previous_position, _) = self.transform_at(time - VELOCITY_SAMPLE_INTERVAL)?;
+
+        let mut hand = pose.skeleton(self.hand_type, position, rotation, self.confidence);
+        hand.id = Some(self.id);
+        if let Some(palm) = hand.palm.as_mut() {
+            palm.velocity = (position - previous_position) / VELOCITY_SAMPLE_INTERVAL;
+        }
+
+        Some(hand)
+    }
+
+    fn transform_at(&self, time: f32) -> Option<(HandPose, Vec3, Quat)> {
+        let first = self.keyframes.first()?;
+        let duration = self.duration();
+        let time = if self.looping && duration > 0.0 {
+            time.rem_euclid(duration)
+        } else {
+            time.clamp(0.0, duration)
+        };
+
+        let next_index = self.keyframes.iter().position(|k| k.time > time);
+        let (from, to, t) = match next_index {
+            None => (self.keyframes.last()?, self.keyframes.last()?, 0.0),
+            Some(0) => (first, first, 0.0),
+            Some(i) => {
+                let (from, to) = (&self.keyframes[i - 1], &self.keyframes[i]);
+                (from, to, (time - from.time) / (to.time - from.time))
+            }
+        };
+
+        Some((
+            from.pose.lerp(&to.pose, t),
+            from.position.lerp(to.position, t),
+            from.rotation.slerp(to.rotation, t),
+        ))
+    }
+}
@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use bevy::app::{App, Plugin, Update};
+use bevy::math::Vec3;
+use bevy::prelude::IntoSystemConfigs;
+use hand_gestures::models::{HandData, HandType};
+use hand_gestures::source::{update_hands_data, HandTrackingSource, TrackingFrame, UpdateHandsData};
+
+pub use crate::animation::{HandAnimation, HandKeyframe};
+pub use crate::pose::HandPose;
+
+mod animation;
+mod pose;
+
+/// Fills [`hand_gestures::HandsData`] from [`ScriptedHandSource`], so the app can run without a tracking device.
+#[derive(Default)]
+pub struct SyntheticInputPlugin {
+    pub source: ScriptedHandSource,
+}
+
+impl Plugin for SyntheticInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_non_send_resource(self.source.clone())
+            .add_systems(Update, update_hands_data::<ScriptedHandSource>.in_set(UpdateHandsData));
+    }
+}
+
+/// [`HandTrackingSource`] which produces frames from hand animations at a fixed rate, like a real tracker.
+#[derive(Clone)]
+pub struct ScriptedHandSource {
+    /// Animations of the hands placed in the first and second slot of [`hand_gestures::TwoHandsData`].
+    pub animations: [Option<HandAnimation>; 2],
+    pub frame_interval: Duration,
+    /// Maximal random offset, in millimetres, added to every joint.
+    pub jitter: f32,
+    /// Hands with confidence below this value are not reported.
+    pub min_confidence: f32,
+    elapsed: Duration,
+    next_frame_at: Duration,
+    rng_state: u64,
+}
+
+impl ScriptedHandSource {
+    pub fn new(first_hand: Option<HandAnimation>, second_hand: Option<HandAnimation>) -> Self {
+        Self {
+            animations: [first_hand, second_hand],
+            frame_interval: Duration::from_secs_f32(1.0 / 90.0),
+            jitter: 0.0,
+            min_confidence: 0.0,
+            elapsed: Duration::ZERO,
+            next_frame_at: Duration::ZERO,
+            rng_state: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    pub fn with_jitter(mut self, jitter: f32, seed: u64) -> Self {
+        self.jitter = jitter;
+        // xorshift gets stuck on zero
+        self.rng_state = seed.max(1);
+        self
+    }
+
+    pub fn with_min_confidence(mut self, min_confidence: f32) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Produces the frame for the given time, without advancing the source.
+    pub fn frame_at(&mut self, time: Duration) -> TrackingFrame {
+        let mut hands = self.animations.each_ref().map(|animation| {
+            animation
+                .as_ref()
+                .and_then(|animation| animation.sample(time.as_secs_f32()))
+                .filter(|hand| hand.confidence >= self.min_confidence)
+        });
+        for hand in hands.iter_mut().flatten() {
+            self.apply_jitter(hand);
+        }
+
+        TrackingFrame {
+            frame_id: (time.as_micros() / self.frame_interval.as_micros().max(1)) as i64,
+            timestamp: time.as_micros() as i64,
+            hands,
+        }
+    }
+
+    fn apply_jitter(&mut self, hand: &mut HandData) {
+        if self.jitter <= 0.0 {
+            return;
+        }
+
+        for finger in [
+            &mut hand.thumb,
+            &mut hand.index,
+            &mut hand.middle,
+            &mut hand.ring,
+            &mut hand.pinky,
+        ] {
+            for joint in finger.iter_mut() {
+                *joint += Vec3::new(self.next_random(), self.next_random(), self.next_random()) * self.jitter;
+            }
+        }
+    }
+
+    /// Returns a pseudo-random value from [-1, 1].
+    fn next_random(&mut self) -> f32 {
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 7;
+        self.rng_state ^= self.rng_state << 17;
+
+        (self.rng_state >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+    }
+}
+
+impl Default for ScriptedHandSource {
+    /// Right hand repeatedly pinching and left hand switching between fist and pointing.
+    fn default() -> Self {
+        let right_hand = HandAnimation::new(HandType::Right)
+            .with_keyframe(0.0, HandPose::OPEN, Vec3::new(80.0, 200.0, 0.0))
+            .with_keyframe(1.0, HandPose::OPEN, Vec3::new(80.0, 200.0, 0.0))
+            .with_keyframe(1.3, HandPose::PINCH, Vec3::new(80.0, 200.0, 0.0))
+            .with_keyframe(1.6, HandPose::PINCH, Vec3::new(100.0, 220.0, -40.0))
+            .with_keyframe(1.9, HandPose::OPEN, Vec3::new(100.0, 220.0, -40.0))
+            .with_keyframe(3.0, HandPose::OPEN, Vec3::new(80.0, 200.0, 0.0));
+        let left_hand = HandAnimation::new(HandType::Left)
+            .with_keyframe(0.0, HandPose::FIST, Vec3::new(-80.0, 200.0, 0.0))
+            .with_keyframe(1.5, HandPose::POINT, Vec3::new(-80.0, 200.0, 0.0))
+            .with_keyframe(3.0, HandPose::FIST, Vec3::new(-80.0, 200.0, 0.0));
+
+        Self::new(Some(right_hand), Some(left_hand)).with_jitter(0.5, 42)
+    }
+}
+
+impl HandTrackingSource for ScriptedHandSource {
+    fn poll_frames(&mut self, delta: Duration) -> Vec<TrackingFrame> {
+        self.elapsed += delta;
+
+        let mut frames = Vec::new();
+        while self.next_frame_at <= self.elapsed {
+            frames.push(self.frame_at(self.next_frame_at));
+            self.next_frame_at += self.frame_interval.max(Duration::from_micros(1));
+        }
+
+        frames
+    }
+}
@@ -0,0 +1,153 @@
+use bevy::math::{Quat, Vec3};
+use hand_gestures::models::{Arm, Finger, HandData, HandType, Palm};
+
+/// Lengths of metacarpal, proximal, intermediate and distal bones in millimetres.
+const THUMB_BONES: [f32; 4] = [0.0, 42.0, 31.0, 22.0];
+const INDEX_BONES: [f32; 4] = [66.0, 40.0, 23.0, 16.0];
+const MIDDLE_BONES: [f32; 4] = [63.0, 44.0, 27.0, 17.0];
+const RING_BONES: [f32; 4] = [58.0, 41.0, 25.0, 17.0];
+const PINKY_BONES: [f32; 4] = [53.0, 33.0, 18.0, 16.0];
+
+const PALM_WIDTH: f32 = 85.0;
+const ARM_LENGTH: f32 = 260.0;
+const ARM_WIDTH: f32 = 60.0;
+/// Bend of the proximal, intermediate and distal bones of a fully curled finger, in radians.
+const FULL_CURL_ANGLES: [f32; 3] = [1.4, 1.7, 1.1];
+
+/// Parametric description of a hand shape, which can be interpolated.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HandPose {
+    /// Curl of thumb, index, middle, ring and pinky; 0 is straight, 1 is fully curled.
+    pub curls: [f32; 5],
+    /// How close the thumb tip is pulled to the index tip; 1 means touching.
+    pub pinch: f32,
+}
+
+impl HandPose {
+    pub const OPEN: Self = Self {
+        curls: [0.0; 5],
+        pinch: 0.0,
+    };
+    pub const PINCH: Self = Self {
+        curls: [0.3, 0.45, 0.2, 0.2, 0.2],
+        pinch: 1.0,
+    };
+    pub const FIST: Self = Self {
+        curls: [1.0; 5],
+        pinch: 0.0,
+    };
+    pub const POINT: Self = Self {
+        curls: [0.8, 0.0, 1.0, 1.0, 1.0],
+        pinch: 0.0,
+    };
+
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let mut curls = self.curls;
+        for (curl, target) in curls.iter_mut().zip(other.curls) {
+            *curl += (target - *curl) * t;
+        }
+
+        Self {
+            curls,
+            pinch: self.pinch + (other.pinch - self.pinch) * t,
+        }
+    }
+
+    /// Builds hand joints, in the same layout as produced by LeapC.
+    /// The hand is palm down with fingers pointing towards -Z; `position` is the palm center.
+    pub fn skeleton(&self, hand_type: HandType, position: Vec3, rotation: Quat, confidence: f32) -> HandData {
+        let mut thumb = build_thumb(self.curls[0]);
+        let index = build_finger(
+            Vec3::new(-12.0, 0.0, 40.0),
+            Vec3::new(-0.1, 0.0, -1.0),
+            INDEX_BONES,
+            self.curls[1],
+        );
+        let middle = build_finger(
+            Vec3::new(-2.0, 0.0, 40.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            MIDDLE_BONES,
+            self.curls[2],
+        );
+        let ring = build_finger(
+            Vec3::new(8.0, 0.0, 40.0),
+            Vec3::new(0.12, 0.0, -1.0),
+            RING_BONES,
+            self.curls[3],
+        );
+        let pinky = build_finger(
+            Vec3::new(18.0, 0.0, 40.0),
+            Vec3::new(0.25, 0.0, -1.0),
+            PINKY_BONES,
+            self.curls[4],
+        );
+
+        let shift = (index[0] - thumb[0]) * self.pinch.clamp(0.0, 1.0);
+        for (joint, weight) in thumb.iter_mut().zip([1.0, 0.7, 0.35, 0.0, 0.0]) {
+            *joint += shift * weight;
+        }
+
+        let to_world = |mut point: Vec3| {
+            if hand_type == HandType::Left {
+                point.x = -point.x;
+            }
+            position + rotation * point
+        };
+        let wrist = to_world(Vec3::new(0.0, 0.0, 50.0));
+
+        HandData {
+            id: None,
+            type_: hand_type,
+            confidence,
+            thumb: thumb.map(to_world),
+            index: index.map(to_world),
+            middle: middle.map(to_world),
+            ring: ring.map(to_world),
+            pinky: pinky.map(to_world),
+            palm: Some(Palm {
+                position,
+                normal: rotation * Vec3::NEG_Y,
+                direction: rotation * Vec3::NEG_Z,
+                velocity: Vec3::ZERO,
+                width: PALM_WIDTH,
+            }),
+            arm: Some(Arm {
+                wrist,
+                elbow: wrist + rotation * Vec3::new(0.0, -0.3 * ARM_LENGTH, 0.95 * ARM_LENGTH),
+                width: ARM_WIDTH,
+            }),
+            grab_strength: Some(self.curls[1..].iter().sum::<f32>() / 4.0),
+            pinch_strength: Some(self.pinch),
+        }
+    }
+}
+
+/// Returns joints ordered from the tip to the base of the metacarpal.
+fn build_finger(base: Vec3, direction: Vec3, bones: [f32; 4], curl: f32) -> Finger {
+    let mut joints = [base; 5];
+    let mut direction = direction.normalize();
+
+    joints[3] = base + direction * bones[0];
+    for bone in 1..4 {
+        direction = Quat::from_rotation_x(-FULL_CURL_ANGLES[bone - 1] * curl.clamp(0.0, 1.0)) * direction;
+        joints[3 - bone] = joints[4 - bone] + direction * bones[bone];
+    }
+
+    joints
+}
+
+fn build_thumb(curl: f32) -> Finger {
+    let base = Vec3::new(-25.0, -5.0, 30.0);
+    let straight = Vec3::new(-0.7, 0.0, -0.7).normalize();
+    let curled = Vec3::new(1.0, -0.5, -0.2).normalize();
+
+    let mut joints = [base; 5];
+    joints[3] = base + straight * THUMB_BONES[0];
+    for bone in 1..4 {
+        let t = (curl.clamp(0.0, 1.0) * bone as f32 / 3.0).min(1.0);
+        let direction = straight.lerp(curled, t).normalize();
+        joints[3 - bone] = joints[4 - bone] + direction * THUMB_BONES[bone];
+    }
+
+    joints
+}
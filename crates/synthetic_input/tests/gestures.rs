@@ -0,0 +1,536 @@
+use std::time::Duration;
+
+use bevy::ecs::event::ManualEventReader;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use hand_gestures::circle_gesture::CircleGesture;
+use hand_gestures::grab_gesture::{GrabEvent, GrabPhase};
+use hand_gestures::models::HandType;
+use hand_gestures::pinch_gesture::{PinchFinger, PinchGesture, PinchReleased, PinchStarted};
+use hand_gestures::point_gesture::{PointEvent, PointPhase};
+use hand_gestures::static_pose::{classify_pose, PoseChanged, PoseConfig, StaticPose};
+use hand_gestures::swipe_gesture::{SwipeConfig, SwipeDirection, SwipeGesture};
+use hand_gestures::tap_gesture::TapGesture;
+use hand_gestures::two_hand_gesture::{TwoHandPinch, TwoHandPinchStarted};
+use hand_gestures::GesturePlugin;
+use synthetic_input::{HandAnimation, HandPose, ScriptedHandSource, SyntheticInputPlugin};
+
+/// Real time advanced by every update; a bit longer than the frame interval of the source.
+const UPDATE_STEP: Duration = Duration::from_millis(12);
+/// Duration of the animation of `ScriptedHandSource::default`, in which the right hand pinches once.
+const DEFAULT_ANIMATION_LOOP: Duration = Duration::from_secs(3);
+
+fn headless_app(source: ScriptedHandSource) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        GesturePlugin::default(),
+        SyntheticInputPlugin { source },
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(UPDATE_STEP));
+    app
+}
+
+/// Runs the app for `duration` and returns all events of type `E` sent meanwhile.
+fn collect_events<E: Event + Clone>(app: &mut App, duration: Duration) -> Vec<E> {
+    // events sent before are still buffered, they are skipped
+    let mut reader = app.world.resource::<Events<E>>().get_reader_current();
+    let mut events = Vec::new();
+    for _ in 0..duration.as_millis() / UPDATE_STEP.as_millis() {
+        app.update();
+        events.extend(reader.read(app.world.resource::<Events<E>>()).cloned());
+    }
+    events
+}
+
+/// Runs the default animation for `loops` loops and returns the pinch starts and releases.
+fn default_animation_pinches(source: ScriptedHandSource, loops: u32) -> (Vec<PinchStarted>, Vec<PinchReleased>) {
+    let mut app = headless_app(source);
+    let mut started_reader = ManualEventReader::<PinchStarted>::default();
+    let mut released_reader = ManualEventReader::<PinchReleased>::default();
+    let (mut started, mut released) = (Vec::new(), Vec::new());
+    for _ in 0..(DEFAULT_ANIMATION_LOOP * loops).as_millis() / UPDATE_STEP.as_millis() {
+        app.update();
+        started.extend(
+            started_reader
+                .read(app.world.resource::<Events<PinchStarted>>())
+                .cloned(),
+        );
+        released.extend(
+            released_reader
+                .read(app.world.resource::<Events<PinchReleased>>())
+                .cloned(),
+        );
+    }
+    (started, released)
+}
+
+fn assert_right_index_pinches(started: &[PinchStarted], released: &[PinchReleased], count: usize) {
+    assert_eq!(started.len(), count);
+    assert_eq!(released.len(), count);
+    assert!(started
+        .iter()
+        .all(|pinch| pinch.hand_type == HandType::Right && pinch.finger == PinchFinger::Index));
+    assert!(released
+        .iter()
+        .all(|pinch| pinch.hand_type == HandType::Right && pinch.finger == PinchFinger::Index));
+}
+
+#[test]
+fn default_animation_pinches_right_index_finger_once_per_loop() {
+    let (started, released) = default_animation_pinches(ScriptedHandSource::default(), 3);
+
+    assert_right_index_pinches(&started, &released, 3);
+}
+
+#[test]
+fn jittered_animation_pinches_right_index_finger_once_per_loop() {
+    // seed 5 used to start a short pinch of the left index finger while it moved from the fist to pointing
+    for seed in 1..=5 {
+        let (started, released) = default_animation_pinches(ScriptedHandSource::default().with_jitter(2.5, seed), 3);
+
+        assert_right_index_pinches(&started, &released, 3);
+    }
+}
+
+#[test]
+fn short_pinch_is_reported_as_pinch_gesture() {
+    let right_hand = HandAnimation::new(HandType::Right)
+        .with_keyframe(0.0, HandPose::OPEN, Vec3::new(80.0, 200.0, 0.0))
+        .with_keyframe(0.5, HandPose::OPEN, Vec3::new(80.0, 200.0, 0.0))
+        .with_keyframe(0.6, HandPose::PINCH, Vec3::new(80.0, 200.0, 0.0))
+        .with_keyframe(0.8, HandPose::PINCH, Vec3::new(80.0, 200.0, 0.0))
+        .with_keyframe(0.9, HandPose::OPEN, Vec3::new(80.0, 200.0, 0.0))
+        .with_keyframe(2.0, HandPose::OPEN, Vec3::new(80.0, 200.0, 0.0));
+    let mut app = headless_app(ScriptedHandSource::new(Some(right_hand), None));
+
+    let gestures = collect_events::<PinchGesture>(&mut app, Duration::from_millis(1500));
+
+    assert_eq!(gestures.len(), 1);
+    assert_eq!(gestures[0].hand_type, HandType::Right);
+    assert_eq!(gestures[0].finger, PinchFinger::Index);
+}
+
+#[test]
+fn poses_are_classified() {
+    let poses = [
+        (HandPose::OPEN, StaticPose::OpenPalm),
+        (HandPose::FIST, StaticPose::Fist),
+        (HandPose::POINT, StaticPose::Point),
+        (
+            HandPose {
+                curls: [0.0, 1.0, 1.0, 1.0, 1.0],
+                pinch: 0.0,
+            },
+            StaticPose::ThumbsUp,
+        ),
+        (
+            HandPose {
+                curls: [0.8, 0.0, 0.0, 1.0, 1.0],
+                pinch: 0.0,
+            },
+            StaticPose::Peace,
+        ),
+        (
+            HandPose {
+                curls: [0.3, 0.45, 0.0, 0.0, 0.0],
+                pinch: 1.0,
+            },
+            StaticPose::Ok,
+        ),
+        (
+            HandPose {
+                curls: [0.0, 0.0, 1.0, 1.0, 1.0],
+                pinch: 0.0,
+            },
+            StaticPose::FingerGun,
+        ),
+        (
+            HandPose {
+                curls: [0.8, 0.0, 1.0, 1.0, 0.0],
+                pinch: 0.0,
+            },
+            StaticPose::Rock,
+        ),
+    ];
+
+    for (pose, expected) in poses {
+        for hand_type in [HandType::Left, HandType::Right] {
+            let hand = pose.skeleton(hand_type, Vec3::new(0.0, 200.0, 0.0), Quat::IDENTITY, 1.0);
+            assert_eq!(classify_pose(&hand, &PoseConfig::default()), expected, "{pose:?}");
+        }
+    }
+}
+
+#[test]
+fn pose_shorter_than_min_duration_is_not_reported() {
+    let position = Vec3::new(80.0, 200.0, 0.0);
+    // the fist is held for 50 ms at first, then for 1 s
+    let right_hand = HandAnimation::new(HandType::Right)
+        .with_looping(false)
+        .with_keyframe(0.0, HandPose::OPEN, position)
+        .with_keyframe(0.5, HandPose::OPEN, position)
+        .with_keyframe(0.505, HandPose::FIST, position)
+        .with_keyframe(0.555, HandPose::FIST, position)
+        .with_keyframe(0.56, HandPose::OPEN, position)
+        .with_keyframe(1.0, HandPose::OPEN, position)
+        .with_keyframe(1.005, HandPose::FIST, position)
+        .with_keyframe(2.0, HandPose::FIST, position);
+    let mut app = headless_app(ScriptedHandSource::new(Some(right_hand), None));
+
+    let changes = collect_events::<PoseChanged>(&mut app, Duration::from_millis(1500))
+        .into_iter()
+        .map(|change| (change.from, change.to))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        changes,
+        [
+            (StaticPose::Unknown, StaticPose::OpenPalm),
+            (StaticPose::OpenPalm, StaticPose::Fist)
+        ]
+    );
+}
+
+#[test]
+fn reversed_circle_decreases_progress() {
+    const STEPS_PER_TURN: usize = 12;
+    // index finger tip traces two counter-clockwise turns and then goes back, one turn per second
+    let center = Vec3::new(80.0, 200.0, 0.0);
+    let angles = (0..=2 * STEPS_PER_TURN).chain((0..2 * STEPS_PER_TURN).rev());
+    let right_hand = angles.enumerate().fold(
+        HandAnimation::new(HandType::Right).with_looping(false),
+        |animation, (step, angle)| {
+            let angle = angle as f32 * std::f32::consts::TAU / STEPS_PER_TURN as f32;
+            let position = center + 60.0 * Vec3::new(angle.cos(), angle.sin(), 0.0);
+            animation.with_keyframe(step as f32 / STEPS_PER_TURN as f32, HandPose::POINT, position)
+        },
+    );
+    let mut app = headless_app(ScriptedHandSource::new(Some(right_hand), None));
+
+    let circles = collect_events::<CircleGesture>(&mut app, Duration::from_secs(4));
+
+    let max_progress = circles.iter().map(|circle| circle.progress).fold(f32::MIN, f32::max);
+    let last = circles.last().expect("circle was not detected");
+    assert!(max_progress > 1.0, "{max_progress}");
+    assert!(last.progress < max_progress - 0.5, "{} {max_progress}", last.progress);
+    assert!(circles.iter().any(|circle| circle.delta < 0.0));
+}
+
+#[test]
+fn closing_and_opening_hand_is_one_grab() {
+    let position = Vec3::new(80.0, 200.0, 0.0);
+    let right_hand = HandAnimation::new(HandType::Right)
+        .with_looping(false)
+        .with_keyframe(0.0, HandPose::OPEN, position)
+        .with_keyframe(0.5, HandPose::OPEN, position)
+        .with_keyframe(0.8, HandPose::FIST, position)
+        .with_keyframe(2.0, HandPose::FIST, position)
+        .with_keyframe(2.3, HandPose::OPEN, position)
+        .with_keyframe(3.0, HandPose::OPEN, position);
+    let mut app = headless_app(ScriptedHandSource::new(Some(right_hand), None));
+
+    let grabs = collect_events::<GrabEvent>(&mut app, Duration::from_secs(3));
+
+    let phases = grabs.iter().map(|grab| grab.phase).collect::<Vec<_>>();
+    assert_eq!(phases.first(), Some(&GrabPhase::Started));
+    assert_eq!(phases.last(), Some(&GrabPhase::Released));
+    assert!(phases[1..phases.len() - 1]
+        .iter()
+        .all(|phase| *phase == GrabPhase::Held));
+    assert!(grabs.iter().all(|grab| grab.hand_type == HandType::Right));
+    // the grab starts while closing and ends while opening the hand
+    let duration = grabs.last().unwrap().duration;
+    assert!(
+        duration > Duration::from_millis(1200) && duration < Duration::from_millis(1800),
+        "{duration:?}"
+    );
+    assert!(grabs.windows(2).all(|pair| pair[0].duration <= pair[1].duration));
+
+    // halfway the hand is a still fist; the transform is between the palm center and the finger tips
+    let fist = HandPose::FIST.skeleton(HandType::Right, position, Quat::IDENTITY, 1.0);
+    let tips_center = fist.fingers().iter().map(|finger| finger[0]).sum::<Vec3>() / 4.0;
+    let held = &grabs[grabs.len() / 2];
+    assert!(held
+        .transform
+        .translation
+        .abs_diff_eq(position.lerp(tips_center, 0.5), 1e-3));
+    // looking along the palm direction, with the back of the hand up
+    assert!(held.transform.forward().abs_diff_eq(Vec3::NEG_Z, 1e-5));
+    assert!(held.transform.up().abs_diff_eq(Vec3::Y, 1e-5));
+}
+
+#[test]
+fn lost_hand_releases_grab() {
+    let right_hand =
+        HandAnimation::new(HandType::Right).with_keyframe(0.0, HandPose::FIST, Vec3::new(80.0, 200.0, 0.0));
+    let mut app = headless_app(ScriptedHandSource::new(Some(right_hand), None));
+
+    let grabs = collect_events::<GrabEvent>(&mut app, Duration::from_millis(300));
+    assert_eq!(grabs.first().map(|grab| grab.phase), Some(GrabPhase::Started));
+
+    app.world.non_send_resource_mut::<ScriptedHandSource>().animations[0] = None;
+    let grabs = collect_events::<GrabEvent>(&mut app, Duration::from_millis(100));
+    assert_eq!(
+        grabs.iter().map(|grab| grab.phase).collect::<Vec<_>>(),
+        [GrabPhase::Released]
+    );
+}
+
+#[test]
+fn pointing_starts_and_ends_once_with_ray_along_index_finger() {
+    let position = Vec3::new(80.0, 200.0, 0.0);
+    let right_hand = HandAnimation::new(HandType::Right)
+        .with_looping(false)
+        .with_keyframe(0.0, HandPose::OPEN, position)
+        .with_keyframe(0.3, HandPose::OPEN, position)
+        .with_keyframe(0.6, HandPose::POINT, position)
+        .with_keyframe(1.5, HandPose::POINT, position)
+        .with_keyframe(1.8, HandPose::OPEN, position)
+        .with_keyframe(2.0, HandPose::OPEN, position);
+    let mut app = headless_app(ScriptedHandSource::new(Some(right_hand), None));
+
+    let events = collect_events::<PointEvent>(&mut app, Duration::from_secs(2));
+
+    let count = |phase| events.iter().filter(|event| event.phase == phase).count();
+    assert_eq!(count(PointPhase::Started), 1);
+    assert_eq!(count(PointPhase::Ended), 1);
+    assert_eq!(events.first().map(|event| event.phase), Some(PointPhase::Started));
+    assert_eq!(events.last().map(|event| event.phase), Some(PointPhase::Ended));
+    assert!(events.iter().all(|event| event.hand_type == HandType::Right));
+
+    let point = HandPose::POINT.skeleton(HandType::Right, position, Quat::IDENTITY, 1.0);
+    let held = &events[events.len() / 2];
+    assert_eq!(held.phase, PointPhase::Held);
+    assert!(held.ray.origin.abs_diff_eq(point.index[0], 1e-3));
+    assert!(held
+        .ray
+        .direction
+        .abs_diff_eq((point.index[0] - point.index[3]).normalize(), 1e-5));
+}
+
+/// Open right hand which rests, moves by each of `movements` within 0.2 s and rests again;
+/// movements start at the given times, in seconds.
+fn moving_hand(movements: &[(f32, Vec3)]) -> HandAnimation {
+    let mut position = Vec3::new(0.0, 200.0, 0.0);
+    let mut animation =
+        HandAnimation::new(HandType::Right)
+            .with_looping(false)
+            .with_keyframe(0.0, HandPose::OPEN, position);
+    for (start, movement) in movements {
+        animation = animation.with_keyframe(*start, HandPose::OPEN, position);
+        position += *movement;
+        animation = animation.with_keyframe(start + 0.2, HandPose::OPEN, position);
+    }
+    animation
+}
+
+#[test]
+fn fast_movement_is_swipe_in_its_direction() {
+    let directions = [
+        (Vec3::NEG_X, SwipeDirection::Left),
+        (Vec3::X, SwipeDirection::Right),
+        (Vec3::Y, SwipeDirection::Up),
+        (Vec3::NEG_Y, SwipeDirection::Down),
+        (Vec3::NEG_Z, SwipeDirection::TowardScreen),
+        (Vec3::Z, SwipeDirection::AwayFromScreen),
+    ];
+    for (axis, direction) in directions {
+        let right_hand = moving_hand(&[(0.3, axis * 300.0)]);
+        let mut app = headless_app(ScriptedHandSource::new(Some(right_hand), None));
+
+        let swipes = collect_events::<SwipeGesture>(&mut app, Duration::from_millis(1500));
+
+        assert_eq!(swipes.len(), 1, "{direction:?}");
+        let swipe = &swipes[0];
+        assert_eq!(swipe.direction, direction);
+        assert_eq!(swipe.hand, HandType::Right);
+        // averaged over the window, which can include the rest before the movement of 300 mm in 0.2 s
+        assert!(
+            swipe.speed > 600.0 && swipe.speed <= 1501.0,
+            "{direction:?}: {}",
+            swipe.speed
+        );
+        assert!((swipe.end - swipe.start).normalize().abs_diff_eq(axis, 1e-3));
+    }
+}
+
+#[test]
+fn slow_movement_is_not_swipe() {
+    // 300 mm in 1 s is half of the default minimal speed
+    let right_hand = HandAnimation::new(HandType::Right)
+        .with_looping(false)
+        .with_keyframe(0.0, HandPose::OPEN, Vec3::new(0.0, 200.0, 0.0))
+        .with_keyframe(1.0, HandPose::OPEN, Vec3::new(300.0, 200.0, 0.0));
+    let mut app = headless_app(ScriptedHandSource::new(Some(right_hand), None));
+
+    assert!(collect_events::<SwipeGesture>(&mut app, Duration::from_millis(1500)).is_empty());
+}
+
+#[test]
+fn swipe_during_cooldown_is_ignored() {
+    let movements = [(0.3, Vec3::X * 300.0), (1.0, Vec3::NEG_X * 300.0)];
+    let swipe_directions = |config: SwipeConfig| {
+        let mut app = headless_app(ScriptedHandSource::new(Some(moving_hand(&movements)), None));
+        app.insert_resource(config);
+        collect_events::<SwipeGesture>(&mut app, Duration::from_secs(2))
+            .iter()
+            .map(|swipe| swipe.direction)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        swipe_directions(SwipeConfig::default()),
+        [SwipeDirection::Right, SwipeDirection::Left]
+    );
+    let long_cooldown = SwipeConfig {
+        cooldown: Duration::from_secs(2),
+        ..SwipeConfig::default()
+    };
+    assert_eq!(swipe_directions(long_cooldown), [SwipeDirection::Right]);
+}
+
+/// Starting palm positions of the left and the right hand in two hand pinch tests.
+const TWO_HAND_START: (Vec3, Vec3) = (Vec3::new(-100.0, 200.0, 0.0), Vec3::new(100.0, 200.0, 0.0));
+
+/// Point between the index finger and the thumb of a pinching hand with the palm at `position`.
+fn pinch_point(hand_type: HandType, position: Vec3) -> Vec3 {
+    let hand = HandPose::PINCH.skeleton(hand_type, position, Quat::IDENTITY, 1.0);
+    hand.index[0].lerp(hand.thumb[0], 0.5)
+}
+
+/// Both hands pinch at [`TWO_HAND_START`], then move to `end` palm positions between 0.5 s and 1.5 s.
+/// Returns [`TwoHandPinch`] events and the left to right pinch axes at the start and the end.
+fn two_hand_pinch_deltas(end: (Vec3, Vec3)) -> (Vec<TwoHandPinch>, Vec3, Vec3) {
+    let animation = |hand_type, start, end| {
+        HandAnimation::new(hand_type)
+            .with_looping(false)
+            .with_keyframe(0.0, HandPose::PINCH, start)
+            .with_keyframe(0.5, HandPose::PINCH, start)
+            .with_keyframe(1.5, HandPose::PINCH, end)
+            .with_keyframe(2.0, HandPose::PINCH, end)
+    };
+    let source = ScriptedHandSource::new(
+        Some(animation(HandType::Right, TWO_HAND_START.1, end.1)),
+        Some(animation(HandType::Left, TWO_HAND_START.0, end.0)),
+    );
+    let mut app = headless_app(source);
+
+    let started = collect_events::<TwoHandPinchStarted>(&mut app, Duration::from_millis(400));
+    let start_axis = pinch_point(HandType::Right, TWO_HAND_START.1) - pinch_point(HandType::Left, TWO_HAND_START.0);
+    assert_eq!(started.len(), 1);
+    let start_center = pinch_point(HandType::Left, TWO_HAND_START.0) + start_axis / 2.0;
+    assert!(started[0].center.abs_diff_eq(start_center, 1e-3));
+
+    let pinches = collect_events::<TwoHandPinch>(&mut app, Duration::from_millis(1600));
+    let end_axis = pinch_point(HandType::Right, end.1) - pinch_point(HandType::Left, end.0);
+    (pinches, start_axis, end_axis)
+}
+
+fn total_scale(pinches: &[TwoHandPinch]) -> f32 {
+    pinches.iter().map(|pinch| pinch.scale).product()
+}
+
+fn total_rotation(pinches: &[TwoHandPinch]) -> Quat {
+    pinches
+        .iter()
+        .fold(Quat::IDENTITY, |rotation, pinch| pinch.rotation * rotation)
+}
+
+fn total_translation(pinches: &[TwoHandPinch]) -> Vec3 {
+    pinches.iter().map(|pinch| pinch.translation).sum()
+}
+
+#[test]
+fn moving_both_pinches_translates_without_scale_and_rotation() {
+    let movement = Vec3::new(50.0, 100.0, -30.0);
+    let (pinches, start_axis, _) = two_hand_pinch_deltas((TWO_HAND_START.0 + movement, TWO_HAND_START.1 + movement));
+
+    assert!(!pinches.is_empty());
+    assert!(total_translation(&pinches).abs_diff_eq(movement, 1e-2));
+    assert!((total_scale(&pinches) - 1.0).abs() < 1e-4);
+    assert!(total_rotation(&pinches).abs_diff_eq(Quat::IDENTITY, 1e-4));
+    let last = pinches.last().unwrap();
+    assert!(last.center.abs_diff_eq(
+        pinch_point(HandType::Left, TWO_HAND_START.0) + start_axis / 2.0 + movement,
+        1e-2
+    ));
+}
+
+#[test]
+fn spreading_pinches_scales_by_distance_ratio() {
+    let spread = Vec3::X * 100.0;
+    let (pinches, start_axis, end_axis) = two_hand_pinch_deltas((TWO_HAND_START.0 - spread, TWO_HAND_START.1 + spread));
+
+    let expected_scale = end_axis.length() / start_axis.length();
+    assert!(expected_scale > 1.5);
+    assert!(
+        (total_scale(&pinches) - expected_scale).abs() < 1e-3,
+        "{}",
+        total_scale(&pinches)
+    );
+    assert!(total_translation(&pinches).abs_diff_eq(Vec3::ZERO, 1e-2));
+    assert!(total_rotation(&pinches).abs_diff_eq(Quat::IDENTITY, 1e-4));
+}
+
+#[test]
+fn turning_pinches_rotates_their_axis() {
+    // a quarter turn around the vertical axis going through the center between palms
+    let (pinches, start_axis, end_axis) =
+        two_hand_pinch_deltas((Vec3::new(0.0, 200.0, 100.0), Vec3::new(0.0, 200.0, -100.0)));
+
+    let rotation = total_rotation(&pinches);
+    assert!((rotation * start_axis.normalize()).abs_diff_eq(end_axis.normalize(), 1e-3));
+    let (yaw, _, _) = rotation.to_euler(EulerRot::YXZ);
+    assert!(yaw > 1.2, "{yaw}");
+    // palms move along straight lines, so the distance shrinks on the way and grows back
+    assert!((total_scale(&pinches) - end_axis.length() / start_axis.length()).abs() < 1e-3);
+}
+
+/// Pointing right hand which pushes its index finger 40 mm toward the screen in `push` seconds, holds it there
+/// for 0.1 s and takes `back` seconds to return; `None` keeps the finger at the screen.
+fn poking_hand(push: f32, back: Option<f32>) -> (HandAnimation, Vec3) {
+    let position = Vec3::new(80.0, 200.0, 0.0);
+    let pushed = position + Vec3::NEG_Z * 40.0;
+    let mut animation = HandAnimation::new(HandType::Right)
+        .with_looping(false)
+        .with_keyframe(0.0, HandPose::POINT, position)
+        .with_keyframe(0.3, HandPose::POINT, position)
+        .with_keyframe(0.3 + push, HandPose::POINT, pushed)
+        .with_keyframe(0.4 + push, HandPose::POINT, pushed);
+    if let Some(back) = back {
+        animation = animation.with_keyframe(0.4 + push + back, HandPose::POINT, position);
+    }
+    (animation, pushed)
+}
+
+#[test]
+fn quick_poke_is_tap_at_deepest_tip() {
+    let (right_hand, pushed) = poking_hand(0.1, Some(0.1));
+    let mut app = headless_app(ScriptedHandSource::new(Some(right_hand), None));
+
+    let taps = collect_events::<TapGesture>(&mut app, Duration::from_millis(1500));
+
+    assert_eq!(taps.len(), 1);
+    assert_eq!(taps[0].hand, HandType::Right);
+    let tip = HandPose::POINT
+        .skeleton(HandType::Right, pushed, Quat::IDENTITY, 1.0)
+        .index[0];
+    assert!(taps[0].position.abs_diff_eq(tip, 1e-3));
+}
+
+#[test]
+fn slow_push_is_not_tap() {
+    let (right_hand, _) = poking_hand(1.0, Some(1.0));
+    let mut app = headless_app(ScriptedHandSource::new(Some(right_hand), None));
+
+    assert!(collect_events::<TapGesture>(&mut app, Duration::from_secs(3)).is_empty());
+}
+
+#[test]
+fn quick_push_without_return_is_not_tap() {
+    let (right_hand, _) = poking_hand(0.1, None);
+    let mut app = headless_app(ScriptedHandSource::new(Some(right_hand), None));
+
+    assert!(collect_events::<TapGesture>(&mut app, Duration::from_millis(1500)).is_empty());
+}
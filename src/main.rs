@@ -6,11 +6,17 @@ use itertools::Itertools;
 use iyes_perf_ui::{PerfUiCompleteBundle, PerfUiPlugin};
 use std::f32::consts::PI;
 
-use hand_gestures::models::{Finger, HandData, HandType};
-use hand_gestures::pinch_gesture::PinchGesture;
+use hand_gestures::models::{HandData, HandType};
+use hand_gestures::pinch_gesture::{PinchFinger, PinchGesture, PinchMoved, PinchReleased, PinchStarted};
+use hand_gestures::recording::RecordingPlugin;
+use hand_gestures::replay::ReplaySourcePlugin;
+use hand_gestures::source::UpdateHandsData;
 use hand_gestures::{GesturePlugin, HandsData, Rb};
-use leap_input::leaprs::{BoneRef, Connection, DigitRef, EventRef as LeapEvent, HandRef, HandType as LeapHandType};
-use leap_input::{HandJoint, HandPhalange, HandsOrigin, LeapInputPlugin};
+#[cfg(feature = "leap")]
+use leap_input::LeapInputPlugin;
+use leap_input::{HandJoint, HandPhalange, HandVisualsPlugin, HandsOrigin};
+#[cfg(not(feature = "leap"))]
+use synthetic_input::SyntheticInputPlugin;
 
 use crate::lines::{LineList, LineMaterial};
 use crate::scene::ScenePlugin;
@@ -23,31 +29,64 @@ mod utils;
 pub const CAMERA_ORIGIN: Transform = Transform::from_xyz(0., 400., 400.);
 
 fn main() {
-    App::new()
-        .add_plugins((
-            DefaultPlugins.set(WindowPlugin {
-                primary_window: Some(Window {
-                    title: "Hands tracking with bevy!".into(),
-                    name: Some("hans.tracking.app".into()),
-                    window_theme: Some(WindowTheme::Dark),
-                    ..default()
-                }),
+    let mut app = App::new();
+
+    app.add_plugins((
+        DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "Hands tracking with bevy!".into(),
+                name: Some("hans.tracking.app".into()),
+                window_theme: Some(WindowTheme::Dark),
                 ..default()
             }),
-            WorldInspectorPlugin::new(),
-            FrameTimeDiagnosticsPlugin,
-            EntityCountDiagnosticsPlugin,
-            PerfUiPlugin,
-            MaterialPlugin::<LineMaterial>::default(),
-            LeapInputPlugin,
-            GesturePlugin,
-            ScenePlugin,
-        ))
-        .insert_resource(ClearColor(Color::SEA_GREEN))
-        .add_systems(Startup, setup_diagnostics)
-        .add_systems(Update, (update_hands_data_resource, update_hands_position))
-        .add_systems(Update, (spawn_sphere_on_pinch, spawn_line_on_pinch).chain())
-        .run();
+            ..default()
+        }),
+        WorldInspectorPlugin::new(),
+        FrameTimeDiagnosticsPlugin,
+        EntityCountDiagnosticsPlugin,
+        PerfUiPlugin,
+        MaterialPlugin::<LineMaterial>::default(),
+        HandVisualsPlugin,
+        GesturePlugin::default(),
+        RecordingPlugin,
+        ScenePlugin,
+    ))
+    .insert_resource(ClearColor(Color::SEA_GREEN))
+    .add_systems(Startup, setup_diagnostics)
+    .add_systems(Update, update_hands_position.after(UpdateHandsData))
+    .add_systems(
+        Update,
+        (
+            remove_last_point_on_middle_pinch,
+            spawn_sphere_on_pinch,
+            drag_sphere_on_pinch,
+            spawn_line_on_pinch,
+            update_lines_of_moved_points,
+        )
+            .chain(),
+    );
+
+    // `--replay <path>` plays a recorded session instead of the live input
+    let replay = std::env::args()
+        .skip_while(|arg| arg != "--replay")
+        .nth(1)
+        .and_then(|path| match ReplaySourcePlugin::from_file(&path) {
+            Ok(plugin) => Some(plugin),
+            Err(error) => {
+                eprintln!("failed to load recording {path}, using the live input: {error}");
+                None
+            }
+        });
+    if let Some(replay) = replay {
+        app.add_plugins(replay);
+    } else {
+        #[cfg(feature = "leap")]
+        app.add_plugins(LeapInputPlugin);
+        #[cfg(not(feature = "leap"))]
+        app.add_plugins(SyntheticInputPlugin::default());
+    }
+
+    app.run();
 }
 
 #[derive(Component, Eq, PartialEq, Ord, PartialOrd)]
@@ -56,6 +95,10 @@ struct NewShapePoint(usize);
 #[derive(Component, Eq, PartialEq, Ord, PartialOrd)]
 struct NewShapeLine(usize, usize);
 
+/// Marks a point which is moved by a pinching hand.
+#[derive(Component)]
+struct DraggedPoint(HandType);
+
 #[derive(Component)]
 struct HandFrame(usize);
 
@@ -123,57 +166,6 @@ fn spawn_hands_frames_components(
         });
 }
 
-fn map_from_leap_hand(leap_hand: &HandRef) -> HandData {
-    HandData {
-        type_: match leap_hand.hand_type() {
-            LeapHandType::Left => HandType::Left,
-            LeapHandType::Right => HandType::Right,
-        },
-        confidence: leap_hand.confidence,
-        thumb: get_simplified_finger(leap_hand.thumb()),
-        index: get_simplified_finger(leap_hand.index()),
-        middle: get_simplified_finger(leap_hand.middle()),
-        ring: get_simplified_finger(leap_hand.ring()),
-        pinky: get_simplified_finger(leap_hand.pinky()),
-    }
-}
-
-fn get_bones<'a>(digit: &'a DigitRef<'a>) -> [BoneRef<'a>; 4] {
-    [
-        digit.distal(),
-        digit.intermediate(),
-        digit.proximal(),
-        digit.metacarpal(),
-    ]
-}
-
-fn get_simplified_finger(digit: DigitRef) -> Finger {
-    let bones = get_bones(&digit);
-    [
-        Vec3::from_array(bones[0].next_joint().array()),
-        Vec3::from_array(bones[0].prev_joint().array()),
-        Vec3::from_array(bones[1].prev_joint().array()),
-        Vec3::from_array(bones[2].prev_joint().array()),
-        Vec3::from_array(bones[3].prev_joint().array()),
-    ]
-}
-
-fn update_hands_data_resource(mut leap_conn: NonSendMut<Connection>, mut hands_data_res: ResMut<HandsData>) {
-    if let Ok(message) = leap_conn.poll(50) {
-        match &message.event() {
-            LeapEvent::Connection(_) => println!("connection event"),
-            LeapEvent::Device(_) => println!("device event"),
-            LeapEvent::Tracking(e) => {
-                let hand1 = e.hands().get(0).and_then(|hand| Some(map_from_leap_hand(hand)));
-                let hand2 = e.hands().get(1).and_then(|hand| Some(map_from_leap_hand(hand)));
-
-                hands_data_res.historical_data.push_overwrite([hand1, hand2]);
-            }
-            _ => {}
-        }
-    }
-}
-
 fn update_hands_position(
     mut hands_data_res: Res<HandsData>,
     mut joints_query: Query<(&mut Transform, &mut Visibility), (With<HandJoint>, Without<HandPhalange>)>,
@@ -182,8 +174,8 @@ fn update_hands_position(
     let mut joints_query_iter = joints_query.iter_mut();
     let mut phalanges_query_iter = phalanges_query.iter_mut();
 
-    if let Some(hands) = hands_data_res.historical_data.iter().next() {
-        for hand in hands.iter().filter_map(Option::as_ref) {
+    if let Some(frame) = hands_data_res.frames().next() {
+        for hand in frame.hands.iter().filter_map(Option::as_ref) {
             for finger in [hand.thumb, hand.index, hand.middle, hand.ring, hand.pinky] {
                 for (p0, p1) in finger.windows(2).map(|points| (points[0], points[1])) {
                     // finger joint
@@ -241,12 +233,15 @@ fn spawn_sphere_on_pinch(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    mut right_pinch_events: EventReader<PinchGesture>,
+    mut pinch_started_events: EventReader<PinchStarted>,
     new_shape_points: Query<(&Transform, &NewShapePoint)>,
 ) {
     let number_of_points = new_shape_points.iter().len();
 
-    if let Some(event) = right_pinch_events.read().next() {
+    if let Some(event) = pinch_started_events
+        .read()
+        .find(|event| event.finger == PinchFinger::Index)
+    {
         let distance = (event.transform.translation.z - CAMERA_ORIGIN.translation.z).abs();
         let normalized_distance = distance.min(600.0) / 600.0;
 
@@ -270,10 +265,61 @@ fn spawn_sphere_on_pinch(
                 ..default()
             },
             NewShapePoint(number_of_points),
+            DraggedPoint(event.hand_type),
         ));
     }
 }
 
+fn drag_sphere_on_pinch(
+    mut commands: Commands,
+    mut pinch_moved_events: EventReader<PinchMoved>,
+    mut pinch_released_events: EventReader<PinchReleased>,
+    mut dragged_points: Query<(Entity, &mut Transform, &DraggedPoint)>,
+) {
+    for event in pinch_moved_events
+        .read()
+        .filter(|event| event.finger == PinchFinger::Index)
+    {
+        for (_, mut transform, dragged_point) in dragged_points.iter_mut() {
+            if dragged_point.0 == event.hand_type {
+                transform.translation = event.transform.translation;
+            }
+        }
+    }
+
+    for event in pinch_released_events
+        .read()
+        .filter(|event| event.finger == PinchFinger::Index)
+    {
+        for (entity, _, dragged_point) in dragged_points.iter() {
+            if dragged_point.0 == event.hand_type {
+                commands.entity(entity).remove::<DraggedPoint>();
+            }
+        }
+    }
+}
+
+fn remove_last_point_on_middle_pinch(
+    mut commands: Commands,
+    mut pinch_events: EventReader<PinchGesture>,
+    new_shape_points: Query<(Entity, &NewShapePoint)>,
+    new_shape_lines: Query<(Entity, &NewShapeLine)>,
+) {
+    if !pinch_events.read().any(|event| event.finger == PinchFinger::Middle) {
+        return;
+    }
+
+    let Some((entity, last_point)) = new_shape_points.iter().max_by_key(|(_, point)| point.0) else {
+        return;
+    };
+    commands.entity(entity).despawn();
+    for (entity, line) in new_shape_lines.iter() {
+        if line.0 == last_point.0 || line.1 == last_point.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
 fn spawn_line_on_pinch(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -285,16 +331,48 @@ fn spawn_line_on_pinch(
     let number_of_lines = new_shape_lines.iter().len();
 
     if number_of_points > 1 && number_of_lines < number_of_points - 1 {
-        let (largest, second_largest) = find_two_largest(new_shape_points.iter(), |&(_, p)| p);
+        let ((largest, largest_point), (second_largest, second_largest_point)) =
+            find_two_largest(new_shape_points.iter(), |&(_, p)| p);
         commands.spawn((
             MaterialMeshBundle {
                 mesh: meshes.add(LineList {
-                    lines: vec![(largest.0.translation, second_largest.0.translation)],
+                    lines: vec![(largest.translation, second_largest.translation)],
                 }),
                 material: line_materials.add(LineMaterial { color: Color::GREEN }),
                 ..default()
             },
-            NewShapeLine(number_of_points - 1, number_of_points),
+            NewShapeLine(second_largest_point.0, largest_point.0),
         ));
     }
 }
+
+fn update_lines_of_moved_points(
+    mut meshes: ResMut<Assets<Mesh>>,
+    moved_points: Query<&NewShapePoint, Changed<Transform>>,
+    new_shape_points: Query<(&Transform, &NewShapePoint)>,
+    new_shape_lines: Query<(&Handle<Mesh>, &NewShapeLine)>,
+) {
+    for moved_point in moved_points.iter() {
+        for (mesh, line) in new_shape_lines.iter() {
+            if line.0 != moved_point.0 && line.1 != moved_point.0 {
+                continue;
+            }
+
+            let point_position = |index: usize| {
+                new_shape_points
+                    .iter()
+                    .find(|(_, point)| point.0 == index)
+                    .map(|(transform, _)| transform.translation)
+            };
+            if let (Some(start), Some(end)) = (point_position(line.0), point_position(line.1)) {
+                meshes.insert(
+                    mesh,
+                    LineList {
+                        lines: vec![(start, end)],
+                    }
+                    .into(),
+                );
+            }
+        }
+    }
+}
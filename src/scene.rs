@@ -1,7 +1,25 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use bevy::prelude::*;
+use hand_gestures::custom_gesture::{CustomGesture, GestureTemplates, TemplateRecorder};
+use hand_gestures::pinch_calibration::{CalibrationStage, PinchCalibration, PinchCalibrator};
+use hand_gestures::recording::HandsRecorder;
+use hand_gestures::replay::ReplaySource;
+use hand_gestures::swipe_gesture::{SwipeDirection, SwipeGesture};
+use hand_gestures::two_hand_gesture::TwoHandPinch;
+use leap_input::connection::TrackingConnectionState;
 
 use crate::CAMERA_ORIGIN;
 
+const RECORDINGS_DIR: &str = "recordings";
+const GESTURE_TEMPLATES_FILE: &str = "gesture_templates.json";
+const REPLAY_SEEK_STEP: Duration = Duration::from_secs(5);
+const CAMERA_TARGET: Vec3 = Vec3::new(0., 200., 0.);
+/// Closest and farthest distance of the camera from [`CAMERA_TARGET`], when zooming with hands.
+const CAMERA_DISTANCE_RANGE: (f32, f32) = (100., 2000.);
+
 pub struct ScenePlugin;
 
 #[derive(Component)]
@@ -10,8 +28,31 @@ struct PlayerCamera;
 impl Plugin for ScenePlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(CurrentMode::default())
-            .add_systems(Startup, (spawn_light, spawn_camera, spawn_ui_text))
-            .add_systems(Update, (keyboard_input, update_current_mode_text));
+            .add_systems(
+                Startup,
+                (spawn_light, spawn_camera, spawn_ui_text, load_gesture_templates),
+            )
+            .add_systems(
+                Update,
+                (
+                    keyboard_input,
+                    swipe_to_change_mode,
+                    update_current_mode_text,
+                    update_tracker_status_text,
+                    update_calibration_text,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_recording,
+                    replay_controls,
+                    start_pinch_calibration,
+                    record_gesture_template,
+                    print_custom_gestures,
+                ),
+            )
+            .add_systems(Update, move_camera_with_two_hands);
     }
 }
 
@@ -25,6 +66,22 @@ pub enum CurrentMode {
     CreateShape,
 }
 
+impl CurrentMode {
+    fn next(&self) -> Self {
+        match self {
+            CurrentMode::Non => CurrentMode::CreateShape,
+            CurrentMode::CreateShape => CurrentMode::Non,
+        }
+    }
+
+    fn previous(&self) -> Self {
+        match self {
+            CurrentMode::Non => CurrentMode::CreateShape,
+            CurrentMode::CreateShape => CurrentMode::Non,
+        }
+    }
+}
+
 fn spawn_light(mut commands: Commands) {
     commands.spawn(DirectionalLightBundle {
         transform: Transform::from_xyz(50.0, 50.0, 50.0).looking_at(Vec3::ZERO, Vec3::Y),
@@ -39,7 +96,7 @@ fn spawn_light(mut commands: Commands) {
 fn spawn_camera(mut commands: Commands) {
     commands.spawn((
         Camera3dBundle {
-            transform: CAMERA_ORIGIN.looking_at(Vec3::Y * 200., Vec3::Y),
+            transform: CAMERA_ORIGIN.looking_at(CAMERA_TARGET, Vec3::Y),
             ..default()
         },
         PlayerCamera,
@@ -58,7 +115,14 @@ fn spawn_ui_text(mut commands: Commands) {
             TextSection::new("Current mode: ", style.clone()),
             TextSection::new("Non", style.clone()),
             TextSection::new("\n", style.clone()),
-            TextSection::new("A - Start creating a new shape", style.clone()),
+            TextSection::new("A, swipe left/right - Change mode\n", style.clone()),
+            TextSection::new("R - Start/stop recording hands\n", style.clone()),
+            TextSection::new("C - Calibrate pinch\n", style.clone()),
+            TextSection::new("Hold G, Shift+G - Record gesture, its example\n", style.clone()),
+            TextSection::new("Tracker: ", style.clone()),
+            TextSection::new("-", style.clone()),
+            TextSection::new("\nPinch calibration: ", style.clone()),
+            TextSection::new("default", style.clone()),
         ])
         .with_style(Style {
             position_type: PositionType::Absolute,
@@ -70,15 +134,207 @@ fn spawn_ui_text(mut commands: Commands) {
     ));
 }
 
+/// Pinching with both hands orbits the camera around the scene (turning the axis between hands)
+/// and zooms it (moving hands apart or together).
+fn move_camera_with_two_hands(
+    mut two_hand_pinches: EventReader<TwoHandPinch>,
+    mut camera: Query<&mut Transform, With<PlayerCamera>>,
+) {
+    let Ok(mut transform) = camera.get_single_mut() else {
+        return;
+    };
+
+    for pinch in two_hand_pinches.read() {
+        let (yaw, _, _) = pinch.rotation.to_euler(EulerRot::YXZ);
+        transform.rotate_around(CAMERA_TARGET, Quat::from_rotation_y(-yaw));
+
+        let offset = transform.translation - CAMERA_TARGET;
+        let distance = (offset.length() / pinch.scale).clamp(CAMERA_DISTANCE_RANGE.0, CAMERA_DISTANCE_RANGE.1);
+        transform.translation = CAMERA_TARGET + offset.normalize_or_zero() * distance;
+    }
+}
+
 fn update_current_mode_text(current_mode: Res<CurrentMode>, mut text: Query<&mut Text, With<ControlsDesc>>) {
     text.single_mut().sections[3].value = format!("{:?}", *current_mode);
 }
 
+fn update_tracker_status_text(
+    connection_state: Option<Res<TrackingConnectionState>>,
+    mut text: Query<&mut Text, With<ControlsDesc>>,
+) {
+    let status = match connection_state.as_deref() {
+        None => "-".to_string(),
+        Some(TrackingConnectionState::Disconnected) => "disconnected".to_string(),
+        Some(TrackingConnectionState::Connecting { attempt }) => format!("connecting (attempt {attempt})"),
+        Some(TrackingConnectionState::WaitingToRetry { delay, .. }) => {
+            format!("retrying in {:.1}s", delay.as_secs_f32())
+        }
+        Some(TrackingConnectionState::Connected) => "connected".to_string(),
+    };
+
+    text.single_mut().sections[10].value = status;
+}
+
+fn update_calibration_text(
+    calibrator: Res<PinchCalibrator>,
+    calibration: Res<PinchCalibration>,
+    mut text: Query<&mut Text, With<ControlsDesc>>,
+) {
+    if !calibrator.is_changed() && !calibration.is_changed() {
+        return;
+    }
+
+    let status = match (calibrator.stage(), calibrator.error()) {
+        (CalibrationStage::OpenHand, _) => "keep your hand open".to_string(),
+        (CalibrationStage::Pinch, _) => "pinch your thumb and index finger".to_string(),
+        (CalibrationStage::Idle, Some(error)) => format!("failed, {error}"),
+        (CalibrationStage::Idle, None) if calibration.is_user_calibrated => "calibrated".to_string(),
+        (CalibrationStage::Idle, None) => "default".to_string(),
+    };
+
+    text.single_mut().sections[12].value = status;
+}
+
 fn keyboard_input(keys: Res<ButtonInput<KeyCode>>, mut current_mode: ResMut<CurrentMode>) {
     if keys.just_pressed(KeyCode::KeyA) {
-        *current_mode = match *current_mode {
-            CurrentMode::Non => CurrentMode::CreateShape,
-            CurrentMode::CreateShape => CurrentMode::Non,
+        *current_mode = current_mode.next();
+    }
+}
+
+/// Swipe right - next mode, swipe left - previous mode
+fn swipe_to_change_mode(mut swipes: EventReader<SwipeGesture>, mut current_mode: ResMut<CurrentMode>) {
+    for swipe in swipes.read() {
+        match swipe.direction {
+            SwipeDirection::Right => *current_mode = current_mode.next(),
+            SwipeDirection::Left => *current_mode = current_mode.previous(),
+            _ => {}
+        }
+    }
+}
+
+fn toggle_recording(keys: Res<ButtonInput<KeyCode>>, mut recorder: ResMut<HandsRecorder>) {
+    if !keys.just_pressed(KeyCode::KeyR) {
+        return;
+    }
+
+    if recorder.is_recording() {
+        if let Err(error) = recorder.stop() {
+            eprintln!("failed to save recording: {error}");
+        }
+        return;
+    }
+
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = format!("{RECORDINGS_DIR}/session_{seconds}.jsonl");
+    let result = std::fs::create_dir_all(RECORDINGS_DIR)
+        .map_err(Into::into)
+        .and_then(|_| recorder.start(&path));
+
+    match result {
+        Ok(_) => println!("recording hands to {path}"),
+        Err(error) => eprintln!("failed to start recording: {error}"),
+    }
+}
+
+fn load_gesture_templates(mut templates: ResMut<GestureTemplates>) {
+    let path = Path::new(RECORDINGS_DIR).join(GESTURE_TEMPLATES_FILE);
+    if !path.exists() {
+        return;
+    }
+
+    match GestureTemplates::load(&path) {
+        Ok(loaded) => *templates = loaded,
+        Err(error) => eprintln!("failed to load gesture templates: {error}"),
+    }
+}
+
+/// Holding G records a new custom gesture, holding Shift+G records another example of the last one.
+/// Templates are saved on every change, so they can be renamed in the file.
+fn record_gesture_template(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut recorder: ResMut<TemplateRecorder>,
+    mut templates: ResMut<GestureTemplates>,
+) {
+    if keys.just_pressed(KeyCode::KeyG) {
+        let last_name = templates.iter().last().map(|template| template.name.clone());
+        let name = match last_name {
+            Some(name) if keys.pressed(KeyCode::ShiftLeft) => name,
+            _ => {
+                let names = templates
+                    .iter()
+                    .map(|template| template.name.as_str())
+                    .collect::<HashSet<_>>();
+                format!("gesture_{}", names.len() + 1)
+            }
         };
+        recorder.start(name);
+    }
+
+    if !keys.just_released(KeyCode::KeyG) || !recorder.is_recording() {
+        return;
+    }
+    let Some(template) = recorder.stop() else {
+        eprintln!("no movement was recorded");
+        return;
+    };
+    println!("recorded an example of {}", template.name);
+    templates.add(template);
+
+    let path = Path::new(RECORDINGS_DIR).join(GESTURE_TEMPLATES_FILE);
+    let result = std::fs::create_dir_all(RECORDINGS_DIR)
+        .map_err(Into::into)
+        .and_then(|_| templates.save(&path));
+    if let Err(error) = result {
+        eprintln!("failed to save gesture templates: {error}");
+    }
+}
+
+fn print_custom_gestures(mut custom_gestures: EventReader<CustomGesture>) {
+    for gesture in custom_gestures.read() {
+        println!("{:?} hand: {} ({:.2})", gesture.hand, gesture.name, gesture.score);
+    }
+}
+
+fn start_pinch_calibration(keys: Res<ButtonInput<KeyCode>>, mut calibrator: ResMut<PinchCalibrator>) {
+    if keys.just_pressed(KeyCode::KeyC) {
+        calibrator.start();
+    }
+}
+
+/// Space - pause/play, Left/Right - seek, Up/Down - change speed, L - toggle looping
+fn replay_controls(keys: Res<ButtonInput<KeyCode>>, replay: Option<NonSendMut<ReplaySource>>) {
+    let Some(mut replay) = replay else {
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Space) {
+        if replay.is_paused() {
+            replay.play();
+        } else {
+            replay.pause();
+        }
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        let position = replay.position() + REPLAY_SEEK_STEP;
+        replay.seek(position);
+    }
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        let position = replay.position().saturating_sub(REPLAY_SEEK_STEP);
+        replay.seek(position);
+    }
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        let speed = replay.speed() * 2.0;
+        replay.set_speed(speed);
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        let speed = replay.speed() / 2.0;
+        replay.set_speed(speed);
+    }
+    if keys.just_pressed(KeyCode::KeyL) {
+        let looping = replay.is_looping();
+        replay.set_looping(!looping);
     }
 }